@@ -0,0 +1,50 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::dbnet::{fence, prelude::*};
+
+action!(
+    /// Attach a fence token to this connection's writes (or detach the token if none is
+    /// provided). Stale tokens are refused right away
+    fn fence(handle: &mut Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len < 2)?;
+        match act.next_string_owned() {
+            Some(token) => {
+                let token = match token.parse::<u64>() {
+                    Ok(token) => token,
+                    Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                };
+                if !fence::is_current(token) {
+                    return util::err(P::RSTRING_FENCED);
+                }
+                handle.set_fence(Some(token));
+            }
+            None => handle.set_fence(None),
+        }
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+);
@@ -68,10 +68,12 @@ action!(
         let tsymbol = match table.get_model_ref() {
             DataModel::KV(kv) => kv.get_value_tsymbol(),
             DataModel::KVExtListmap(kv) => kv.get_value_tsymbol(),
+            DataModel::Unavailable(_) => return util::err(P::RSTRING_MODEL_UNAVAILABLE),
         };
         let items: Vec<SharedSlice> = match table.get_model_ref() {
             DataModel::KV(kv) => kv.get_inner_ref().get_keys(count),
             DataModel::KVExtListmap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::Unavailable(_) => return util::err(P::RSTRING_MODEL_UNAVAILABLE),
         };
         con.write_typed_non_null_array_header(items.len(), tsymbol)
             .await?;
@@ -49,12 +49,22 @@ macro_rules! is_lowbit_unset {
 #[macro_export]
 macro_rules! get_tbl {
     ($entity:expr, $store:expr, $con:expr) => {{
-        $crate::actions::translate_ddl_error::<P, ::std::sync::Arc<$crate::corestore::table::Table>>(
-            $store.get_table($entity),
-        )?
+        match $crate::actions::translate_ddl_error::<
+            P,
+            ::std::sync::Arc<$crate::corestore::table::Table>,
+        >($store.get_table($entity))?
+        {
+            tbl if tbl.is_unavailable() => {
+                return $crate::util::err(P::RSTRING_MODEL_UNAVAILABLE)
+            }
+            tbl => tbl,
+        }
     }};
     ($store:expr, $con:expr) => {{
         match $store.get_ctable() {
+            Some(tbl) if tbl.is_unavailable() => {
+                return $crate::util::err(P::RSTRING_MODEL_UNAVAILABLE)
+            }
             Some(tbl) => tbl,
             None => return $crate::util::err(P::RSTRING_DEFAULT_UNSET),
         }
@@ -65,6 +75,9 @@ macro_rules! get_tbl {
 macro_rules! get_tbl_ref {
     ($store:expr, $con:expr) => {{
         match $store.get_ctable_ref() {
+            Some(tbl) if tbl.is_unavailable() => {
+                return $crate::util::err(P::RSTRING_MODEL_UNAVAILABLE)
+            }
             Some(tbl) => tbl,
             None => return $crate::util::err(P::RSTRING_DEFAULT_UNSET),
         }
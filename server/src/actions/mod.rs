@@ -32,9 +32,11 @@
 
 #[macro_use]
 mod macros;
+pub mod checksum;
 pub mod dbsize;
 pub mod del;
 pub mod exists;
+pub mod fence;
 pub mod flushdb;
 pub mod get;
 pub mod keylen;
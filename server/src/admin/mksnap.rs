@@ -38,6 +38,10 @@ action!(
     ///
     fn mksnap(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
         let engine = handle.get_engine();
+        if registry::is_ephemeral() {
+            // remote snapshots are otherwise allowed even if snapshots are disabled
+            return util::err(P::RSTRING_SNAPSHOT_DISABLED);
+        }
         if act.is_empty() {
             // traditional mksnap
             match engine.mksnap(handle.clone_store()).await {
@@ -26,14 +26,51 @@
 
 use {
     crate::{
-        corestore::booltable::BoolTable, dbnet::prelude::*,
-        storage::v1::interface::DIR_ROOT,
+        actions::ActionResult,
+        blueql::Entity,
+        corestore::{
+            booltable::BoolTable,
+            memstore::{Memstore, ObjectID, SYSTEM},
+        },
+        dbnet::{
+            capture::{self, StartResult},
+            fence,
+            prelude::*,
+        },
+        services::{bgsave, history},
+        storage::v1::{diff, interface::DIR_ROOT, unflush},
     },
+    core::time::Duration,
     libsky::VERSION,
+    std::{
+        fs,
+        io::{ErrorKind, Result as IoResult},
+        path::{Component, Path},
+    },
+    tokio::task,
 };
 
 const INFO: &[u8] = b"info";
 const METRIC: &[u8] = b"metric";
+const FLUSH: &[u8] = b"flush";
+const QUIESCE: &[u8] = b"quiesce";
+const QUIESCE_BEGIN: &[u8] = b"begin";
+const QUIESCE_END: &[u8] = b"end";
+/// The default duration (in seconds) after which writes are automatically resumed
+const QUIESCE_DEFAULT_TIMEOUT: u64 = 300;
+const DDL: &[u8] = b"ddl";
+const DIFF: &[u8] = b"diff";
+const CAPTURE: &[u8] = b"capture";
+const CAPTURE_START: &[u8] = b"start";
+const CAPTURE_STOP: &[u8] = b"stop";
+const CAPTURE_RAW: &[u8] = b"raw";
+const REPORT: &[u8] = b"report";
+const REPORT_STORAGE: &[u8] = b"storage";
+const REPORT_HEALTH: &[u8] = b"health";
+const REPORT_HISTORY: &[u8] = b"history";
+const FENCE: &[u8] = b"fence";
+const FENCE_ACQUIRE: &[u8] = b"acquire";
+const FENCE_RELEASE: &[u8] = b"release";
 const INFO_PROTOCOL: &[u8] = b"protocol";
 const INFO_PROTOVER: &[u8] = b"protover";
 const INFO_VERSION: &[u8] = b"version";
@@ -41,19 +78,270 @@ const METRIC_HEALTH: &[u8] = b"health";
 const METRIC_STORAGE_USAGE: &[u8] = b"storage";
 const ERR_UNKNOWN_PROPERTY: &[u8] = b"!16\nunknown-property\n";
 const ERR_UNKNOWN_METRIC: &[u8] = b"!14\nunknown-metric\n";
+const ERR_UNKNOWN_REPORT: &[u8] = b"!14\nunknown-report\n";
 
 const HEALTH_TABLE: BoolTable<&str> = BoolTable::new("good", "critical");
 
 action! {
-    fn sys(_handle: &Corestore, con: &mut Connection<C, P>, iter: ActionIter<'_>) {
+    fn sys(handle: &Corestore, con: &mut Connection<C, P>, iter: ActionIter<'_>) {
         let mut iter = iter;
-        ensure_boolean_or_aerr::<P>(iter.len() == 2)?;
+        ensure_length::<P>(iter.len(), |len| (1..=5).contains(&len))?;
         match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            INFO | METRIC | DDL | DIFF | REPORT if iter.len() != 1 => {
+                util::err(P::RCODE_ACTION_ERR)
+            }
+            FLUSH if iter.len() > 1 => util::err(P::RCODE_ACTION_ERR),
+            QUIESCE if iter.is_empty() || iter.len() > 2 => util::err(P::RCODE_ACTION_ERR),
+            CAPTURE if iter.is_empty() => util::err(P::RCODE_ACTION_ERR),
+            FENCE if iter.is_empty() || iter.len() > 2 => util::err(P::RCODE_ACTION_ERR),
+            // nothing is ever written to disk in ephemeral mode
+            FLUSH | QUIESCE | CAPTURE if registry::is_ephemeral() => {
+                util::err(P::RSTRING_EPHEMERAL)
+            }
             INFO => sys_info(con, &mut iter).await,
             METRIC => sys_metric(con, &mut iter).await,
+            FLUSH => sys_flush(handle, con, &mut iter).await,
+            QUIESCE => sys_quiesce(handle, con, &mut iter).await,
+            CAPTURE => sys_capture(con, &mut iter).await,
+            DDL => sys_ddl(handle, con, &mut iter).await,
+            DIFF => sys_diff(handle, con, &mut iter).await,
+            REPORT => sys_report(handle, con, &mut iter).await,
+            FENCE => sys_fence(con, &mut iter).await,
             _ => util::err(P::RCODE_UNKNOWN_ACTION),
         }
     }
+    /// Pause writes and flush all data (or resume writes) so that a consistent filesystem-level
+    /// snapshot can be taken
+    fn sys_quiesce(handle: &Corestore, con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            QUIESCE_BEGIN => {
+                let timeout = match iter.next_string_owned() {
+                    Some(timeout) => match timeout.parse::<u64>() {
+                        Ok(timeout) if timeout > 0 => timeout,
+                        _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+                    },
+                    None => QUIESCE_DEFAULT_TIMEOUT,
+                };
+                registry::begin_quiesce(Duration::from_secs(timeout));
+                let handle = handle.clone();
+                let flush = move || {
+                    // writes that started before the pause have to land before we flush
+                    registry::wait_for_writes() && bgsave::bgsave_blocking_section(handle)
+                };
+                match task::spawn_blocking(flush).await {
+                    Ok(true) => {
+                        log::info!("Writes paused for at most {timeout} seconds");
+                        con._write_raw(P::RCODE_OKAY).await?
+                    }
+                    _ => {
+                        // the data isn't consistent on disk, so there's no point in pausing writes
+                        registry::end_quiesce();
+                        return util::err(P::RCODE_SERVER_ERR);
+                    }
+                }
+            }
+            QUIESCE_END => {
+                ensure_boolean_or_aerr::<P>(iter.is_empty())?;
+                if registry::end_quiesce() {
+                    log::info!("Writes resumed");
+                    con._write_raw(P::RCODE_OKAY).await?
+                } else {
+                    return util::err(P::RSTRING_NOT_QUIESCED);
+                }
+            }
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+    /// Start capturing queries (with their timings) into a capture file, or stop the active
+    /// capture (returning the number of captured queries)
+    fn sys_capture(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            CAPTURE_START => {
+                ensure_length::<P>(iter.len(), |len| (1..=3).contains(&len))?;
+                let name = unsafe { iter.next_unchecked() };
+                if !capture::is_legal_name(name) {
+                    return util::err(P::RCODE_ACTION_ERR);
+                }
+                let every = match iter.next_string_owned() {
+                    Some(every) => match every.parse::<u64>() {
+                        Ok(every) if every > 0 => every,
+                        _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+                    },
+                    None => 1,
+                };
+                let hashed = match iter.next_lowercase() {
+                    Some(raw) if raw.as_ref() == CAPTURE_RAW => false,
+                    Some(_) => return util::err(P::RCODE_ACTION_ERR),
+                    None => true,
+                };
+                let name = unsafe {
+                    // UNSAFE(@ohsayan): we just checked that the name is ASCII
+                    core::str::from_utf8_unchecked(name)
+                };
+                match capture::start(name, every, hashed) {
+                    StartResult::Started => {
+                        log::info!("Started capture `{name}` (sampling 1 in {every} queries)");
+                        con._write_raw(P::RCODE_OKAY).await?
+                    }
+                    StartResult::AlreadyActive => return util::err(P::RSTRING_CAPTURE_ACTIVE),
+                    StartResult::AlreadyExists => return util::err(P::RSTRING_CAPTURE_EXISTS),
+                    StartResult::IoError(e) => {
+                        log::error!("Failed to start capture with: {e}");
+                        return util::err(P::RCODE_SERVER_ERR);
+                    }
+                }
+            }
+            CAPTURE_STOP => {
+                ensure_boolean_or_aerr::<P>(iter.is_empty())?;
+                match capture::stop() {
+                    Some(Ok(captured)) => {
+                        log::info!("Stopped capture after capturing {captured} queries");
+                        con.write_int64(captured).await?
+                    }
+                    Some(Err(e)) => {
+                        log::error!("Failed to flush capture file with: {e}");
+                        return util::err(P::RCODE_SERVER_ERR);
+                    }
+                    None => return util::err(P::RSTRING_NOT_CAPTURING),
+                }
+            }
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+    /// Acquire a new fence (returning its token) or release the fence with the given token
+    fn sys_fence(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            FENCE_ACQUIRE => {
+                ensure_boolean_or_aerr::<P>(iter.is_empty())?;
+                match fence::acquire() {
+                    Ok(token) => {
+                        log::info!("Acquired fence {token}");
+                        con.write_int64(token).await?
+                    }
+                    Err(e) => {
+                        log::error!("Failed to persist fence with: {e}");
+                        return util::err(P::RCODE_SERVER_ERR);
+                    }
+                }
+            }
+            FENCE_RELEASE => {
+                ensure_length::<P>(iter.len(), |len| len == 1)?;
+                let token = match unsafe { iter.next_string_owned().unsafe_unwrap() }.parse() {
+                    Ok(token) => token,
+                    Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                };
+                match fence::release(token) {
+                    Ok(true) => {
+                        log::info!("Released fence {token}");
+                        con._write_raw(P::RCODE_OKAY).await?
+                    }
+                    Ok(false) => return util::err(P::RSTRING_FENCED),
+                    Err(e) => {
+                        log::error!("Failed to persist fence with: {e}");
+                        return util::err(P::RCODE_SERVER_ERR);
+                    }
+                }
+            }
+            _ => return util::err(P::RCODE_UNKNOWN_ACTION),
+        }
+        Ok(())
+    }
+    /// Return the `create model` statement that recreates the given model
+    fn sys_ddl(handle: &Corestore, con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        let raw_entity = unsafe { iter.next_unchecked() };
+        let entity = handle_entity!(con, raw_entity);
+        let table = translate_ddl_error::<P, _>(handle.get_table(&entity))?;
+        let (ksid, tblid) = resolve_entity::<P>(handle, entity.as_ref())?;
+        let ddl = unsafe {
+            // UNSAFE(@ohsayan): object IDs are always valid UTF-8
+            format!(
+                "create model {}.{}{}{}",
+                ksid.as_str(),
+                tblid.as_str(),
+                table.model_declaration(),
+                if table.is_volatile() { " volatile" } else { "" }
+            )
+        };
+        con.write_string(&ddl).await?;
+        Ok(())
+    }
+    /// Compare a backup (relative to the data directory) with the live data, returning the
+    /// drift that was found
+    fn sys_diff(handle: &Corestore, con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        let backup = match iter.next_string_owned() {
+            Some(backup) if is_legal_backup_path(&backup) => Path::new(DIR_ROOT).join(backup),
+            _ => return util::err(P::RCODE_ACTION_ERR),
+        };
+        if !backup.is_dir() {
+            return util::err(P::RSTRING_BACKUP_NOT_FOUND);
+        }
+        let handle = handle.clone();
+        let drift = task::spawn_blocking(move || diff::diff_backup(&backup, handle.get_store()))
+            .await;
+        match drift {
+            Ok(Ok(drift)) => con.write_typed_non_null_array(drift, b'+').await?,
+            Ok(Err(e)) => {
+                log::error!("Failed to compare backup with: {e}");
+                return util::err(P::RCODE_SERVER_ERR);
+            }
+            Err(_) => return util::err(P::RCODE_SERVER_ERR),
+        }
+        Ok(())
+    }
+    /// Flush all the data (or just the given model) to disk, returning once it is durable
+    fn sys_flush(handle: &Corestore, con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        let flushed = if iter.is_empty() {
+            let handle = handle.clone();
+            task::spawn_blocking(move || bgsave::bgsave_blocking_section(handle)).await
+        } else {
+            let raw_entity = unsafe { iter.next_unchecked() };
+            let entity = handle_entity!(con, raw_entity);
+            let table = translate_ddl_error::<P, _>(handle.get_table(&entity))?;
+            let (ksid, tblid) = resolve_entity::<P>(handle, entity.as_ref())?;
+            let handle = handle.clone();
+            task::spawn_blocking(move || {
+                bgsave::flush_table_blocking(handle, &ksid, &tblid, &table)
+            })
+            .await
+        };
+        match flushed {
+            Ok(true) => con._write_raw(P::RCODE_OKAY).await?,
+            _ => return util::err(P::RCODE_SERVER_ERR),
+        }
+        Ok(())
+    }
+    /// Return a report on the given subsystem
+    fn sys_report(handle: &Corestore, con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+        match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
+            REPORT_STORAGE => {
+                let handle = handle.clone();
+                match task::spawn_blocking(move || storage_report(handle.get_store())).await {
+                    Ok(Ok(report)) => con.write_typed_non_null_array(report, b'+').await?,
+                    Ok(Err(e)) => {
+                        log::error!("Failed to generate storage report with: {e}");
+                        return util::err(P::RCODE_SERVER_ERR);
+                    }
+                    Err(_) => return util::err(P::RCODE_SERVER_ERR),
+                }
+            }
+            REPORT_HEALTH => match task::spawn_blocking(health_report).await {
+                Ok(Ok(report)) => con.write_typed_non_null_array(report, b'+').await?,
+                Ok(Err(e)) => {
+                    log::error!("Failed to generate health report with: {e}");
+                    return util::err(P::RCODE_SERVER_ERR);
+                }
+                Err(_) => return util::err(P::RCODE_SERVER_ERR),
+            },
+            REPORT_HISTORY => {
+                con.write_typed_non_null_array(history::report(), b'+')
+                    .await?
+            }
+            _ => return util::err(ERR_UNKNOWN_REPORT),
+        }
+        Ok(())
+    }
     fn sys_info(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
         match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
             INFO_PROTOCOL => con.write_string(P::PROTOCOL_VERSIONSTRING).await?,
@@ -66,7 +354,7 @@ action! {
     fn sys_metric(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
         match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
             METRIC_HEALTH => {
-                con.write_string(HEALTH_TABLE[registry::state_okay()]).await?
+                con.write_string(HEALTH_TABLE[registry::health_okay()]).await?
             }
             METRIC_STORAGE_USAGE => {
                 match util::os::dirsize(DIR_ROOT) {
@@ -82,3 +370,104 @@ action! {
         Ok(())
     }
 }
+
+/// Resolve the space and model IDs for the given entity
+fn resolve_entity<P: ProtocolSpec>(
+    handle: &Corestore,
+    entity: &Entity,
+) -> ActionResult<(ObjectID, ObjectID)> {
+    unsafe {
+        // UNSAFE(@ohsayan): the entity names have already been validated by the compiler
+        match entity {
+            Entity::Full(ks, tbl) => Ok((
+                ObjectID::from_slice(ks.as_slice()),
+                ObjectID::from_slice(tbl.as_slice()),
+            )),
+            Entity::Current(tbl) => match handle.get_ids() {
+                (Some(ks), _) => Ok((ks.clone(), ObjectID::from_slice(tbl.as_slice()))),
+                _ => util::err(P::RSTRING_DEFAULT_UNSET),
+            },
+        }
+    }
+}
+
+/// Describe the state of this node: its health, whether it accepts writes, the disk usage and
+/// when data was last written to disk
+fn health_report() -> IoResult<Vec<String>> {
+    let writes = if !registry::health_okay() {
+        "refused"
+    } else if registry::is_read_only() {
+        "read-only"
+    } else if registry::is_quiesced() {
+        "paused"
+    } else {
+        "accepted"
+    };
+    let mut report = vec![
+        format!("health: {}", HEALTH_TABLE[registry::health_okay()]),
+        format!("writes: {writes}"),
+    ];
+    if registry::is_ephemeral() {
+        report.push("persistence: ephemeral".to_owned());
+        return Ok(report);
+    }
+    report.push("persistence: disk".to_owned());
+    report.push(format!("data size: {} bytes", util::os::dirsize(DIR_ROOT)?));
+    report.push(format!(
+        "disk free: {} bytes",
+        util::os::free_disk_space(DIR_ROOT)?
+    ));
+    report.push(match registry::secs_since_last_save() {
+        Some(secs) => format!("last save: {secs} seconds ago"),
+        None => "last save: never".to_owned(),
+    });
+    Ok(report)
+}
+
+/// Describe the storage used by every model: its storage type, the size of its data file and
+/// the number of rows that it holds
+fn storage_report(store: &Memstore) -> IoResult<Vec<String>> {
+    let mut report = Vec::new();
+    for ksid in store.list_keyspaces() {
+        if ksid == SYSTEM {
+            continue;
+        }
+        let ks = match store.get_keyspace_atomic_ref(&ksid) {
+            Some(ks) => ks,
+            None => continue,
+        };
+        let ksname = unsafe { ksid.as_str() };
+        for tbl in ks.tables.iter() {
+            let tblname = unsafe { tbl.key().as_str() };
+            let table = tbl.value();
+            let size = match fs::metadata(unflush::table_path(&ksid, tbl.key())) {
+                Ok(metadata) => metadata.len(),
+                // volatile models (and models that haven't been flushed yet) have no data file
+                Err(e) if e.kind() == ErrorKind::NotFound => 0,
+                Err(e) => return Err(e),
+            };
+            let state = if table.is_unavailable() {
+                "unavailable"
+            } else if table.is_volatile() {
+                "volatile"
+            } else {
+                "persistent"
+            };
+            report.push(format!(
+                "{ksname}.{tblname}: {state}, {size} bytes on disk, {} rows",
+                table.count()
+            ));
+        }
+    }
+    Ok(report)
+}
+
+/// Check if the path to a backup is legal. It must be relative to the data directory and
+/// must not point outside it
+fn is_legal_backup_path(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
@@ -27,25 +27,30 @@
 use {
     crate::{
         auth::AuthProvider,
-        config::{ConfigurationSet, SnapshotConfig, SnapshotPref},
+        config::{BGSave, ConfigurationSet, SnapshotConfig, SnapshotPref},
         corestore::Corestore,
         dbnet,
         diskstore::flock::FileLock,
-        services,
-        storage::v1::sengine::SnapshotEngine,
+        protocol::set_query_limits,
+        registry, services,
+        storage::v1::{sengine::SnapshotEngine, unflush},
         util::{
             error::{Error, SkyResult},
             os::TerminationSignal,
         },
     },
-    std::{sync::Arc, thread::sleep},
+    std::{
+        net::{IpAddr, Ipv4Addr},
+        sync::Arc,
+        thread::sleep,
+    },
     tokio::{
         sync::{
             broadcast,
             mpsc::{self, Sender},
         },
         task::{self, JoinHandle},
-        time::Duration,
+        time::{self, Duration},
     },
 };
 
@@ -58,26 +63,107 @@ pub async fn run(
         bgsave,
         snapshot,
         maxcon,
+        max_pipeline,
         auth,
         protocol,
+        storage,
+        health_port,
+        metrics_endpoint,
+        ephemeral,
+        admin_socket,
+        readonly,
+        admin_port,
+        admin_local,
+        tcp,
+        ratelimit,
+        limits,
+        conformance,
+        init_dir,
+        drain_timeout,
         ..
     }: ConfigurationSet,
     restore_filepath: Option<String>,
+    pid_file: Option<FileLock>,
 ) -> SkyResult<Corestore> {
+    set_query_limits(limits);
     // Intialize the broadcast channel
     let (signal, _) = broadcast::channel(1);
-    let engine = match &snapshot {
-        SnapshotConfig::Enabled(SnapshotPref { atmost, .. }) => SnapshotEngine::new(*atmost),
-        SnapshotConfig::Disabled => SnapshotEngine::new_disabled(),
+    // start the health probe before restoring data so that liveness checks pass while we load
+    let health_handle = if health_port == 0 {
+        None
+    } else {
+        let listener = services::health::bind(ports.get_host(), health_port).await?;
+        Some(tokio::spawn(services::health::health_service(
+            listener,
+            signal.subscribe(),
+        )))
     };
-    let engine = Arc::new(engine);
-    // restore data
-    services::restore_data(restore_filepath)
-        .map_err(|e| Error::ioerror_extra(e, "restoring data from backup"))?;
-    // init the store
-    let db = Corestore::init_with_snapcfg(engine.clone())?;
-    // refresh the snapshotengine state
-    engine.parse_dir()?;
+    let metrics_handle = match metrics_endpoint {
+        Some(endpoint) => {
+            let listener = services::metrics::bind(endpoint).await?;
+            Some(tokio::spawn(services::metrics::metrics_service(
+                listener,
+                signal.subscribe(),
+            )))
+        }
+        None => None,
+    };
+    let (db, engine, bgsave, snapshot) = if ephemeral || conformance {
+        log::warn!("Running in ephemeral mode. NO DATA WILL BE PERSISTED TO DISK");
+        if restore_filepath.is_some() {
+            log::warn!("Ignoring the backup to restore from since we're running in ephemeral mode");
+        }
+        if matches!(snapshot, SnapshotConfig::Enabled(_)) {
+            log::warn!("Snapshots are disabled since we're running in ephemeral mode");
+        }
+        registry::set_ephemeral();
+        let engine = Arc::new(SnapshotEngine::new_disabled());
+        let db = Corestore::init_ephemeral(engine.clone());
+        // every ephemeral instance is a new instance
+        if let Some(dir) = &init_dir {
+            services::seed::run_seeds(&db, dir)?;
+        }
+        (db, engine, BGSave::Disabled, SnapshotConfig::Disabled)
+    } else {
+        let engine = match &snapshot {
+            SnapshotConfig::Enabled(SnapshotPref {
+                atmost, archive, ..
+            }) => SnapshotEngine::new(*atmost, archive.clone()),
+            SnapshotConfig::Disabled => SnapshotEngine::new_disabled(),
+        };
+        let engine = Arc::new(engine);
+        // restore data
+        services::restore_data(restore_filepath)
+            .map_err(|e| Error::ioerror_extra(e, "restoring data from backup"))?;
+        // seed scripts only run when the instance is first initialized
+        let new_instance = unflush::is_new_instance()?;
+        // init the store
+        let db = Corestore::init_with_snapcfg(engine.clone(), &storage)?;
+        match &init_dir {
+            Some(dir) if new_instance => {
+                services::seed::run_seeds(&db, dir)?;
+                // persist the seeded schema right away
+                services::bgsave::run_bgsave(&db)
+                    .map_err(|e| Error::ioerror_extra(e, "flushing the seeded data"))?;
+            }
+            Some(_) => log::info!("Skipping the seed scripts since this isn't a new instance"),
+            None => {}
+        }
+        // refresh the snapshotengine state
+        engine.parse_dir()?;
+        // load the latest fence
+        dbnet::fence::load().map_err(|e| Error::ioerror_extra(e, "loading the latest fence"))?;
+        (db, engine, bgsave, snapshot)
+    };
+    if conformance {
+        log::warn!("Running in conformance mode. Fixtures are loaded and faults can be scripted");
+        dbnet::conformance::enable(&db);
+    }
+    if readonly {
+        log::warn!("Running in read-only mode. All writes will be refused");
+        registry::set_read_only();
+    }
+    registry::get_loaded_tripswitch().trip();
     let auth_provider = match auth.origin_key {
         Some(key) => {
             let authref = db.get_store().setup_auth();
@@ -92,6 +178,13 @@ pub async fn run(
         bgsave,
         signal.subscribe(),
     ));
+    let history_handle = tokio::spawn(services::history::history_service(signal.subscribe()));
+    let heartbeat_handle = pid_file.map(|pid_file| {
+        tokio::spawn(services::lease::heartbeat_service(
+            pid_file,
+            signal.subscribe(),
+        ))
+    });
     let snapshot_handle = tokio::spawn(services::snapshot::snapshot_service(
         engine,
         db.clone(),
@@ -102,30 +195,127 @@ pub async fn run(
     // bind to signals
     let termsig =
         TerminationSignal::init().map_err(|e| Error::ioerror_extra(e, "binding to signals"))?;
+    // start the admin socket, if enabled
+    #[cfg(unix)]
+    let mut admin_listener = match admin_socket {
+        Some(path) => Some(dbnet::local::LocalListener::bind(
+            path,
+            &db,
+            auth_provider.clone(),
+            signal.clone(),
+        )?),
+        None => None,
+    };
+    #[cfg(not(unix))]
+    let _ = admin_socket;
+    // start the admin endpoint, if enabled
+    let mut admin_endpoint = if admin_port == 0 {
+        None
+    } else {
+        let host = if admin_local {
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        } else {
+            ports.get_host()
+        };
+        Some(
+            dbnet::connect_admin(
+                host,
+                admin_port,
+                protocol,
+                tcp,
+                db.clone(),
+                auth_provider.clone(),
+                signal.clone(),
+            )
+            .await?,
+        )
+    };
     // start the server (single or multiple listeners)
     let mut server = dbnet::connect(
         ports,
         protocol,
         maxcon,
+        max_pipeline,
+        tcp,
+        ratelimit,
         db.clone(),
         auth_provider,
         signal.clone(),
     )
     .await?;
 
+    let run_admin = async {
+        #[cfg(unix)]
+        if let Some(admin_listener) = admin_listener.as_mut() {
+            if let Err(e) = admin_listener.run().await {
+                log::error!("Admin socket failed with: {e}");
+            }
+        }
+        // don't take the server down if the admin socket isn't available
+        core::future::pending::<()>().await
+    };
+    let run_admin_endpoint = async {
+        if let Some(admin_endpoint) = admin_endpoint.as_mut() {
+            if let Err(e) = admin_endpoint.run_server().await {
+                log::error!("Admin endpoint failed with: {e}");
+            }
+        }
+        // similarly, don't take the server down if the admin endpoint isn't available
+        core::future::pending::<()>().await
+    };
     tokio::select! {
         _ = server.run_server() => {},
+        _ = run_admin => {},
+        _ = run_admin_endpoint => {},
         _ = termsig => {}
     }
 
     log::info!("Signalling all workers to shut down");
     // drop the signal and let others exit
     drop(signal);
-    server.finish_with_termsig().await;
+    // connections are only signalled once every listener has let go of the signal, so release
+    // all of them together
+    let drain = async {
+        tokio::join!(
+            server.finish_with_termsig(),
+            async {
+                if let Some(admin_endpoint) = admin_endpoint {
+                    admin_endpoint.finish_with_termsig().await;
+                }
+            },
+            async {
+                #[cfg(unix)]
+                if let Some(admin_listener) = admin_listener {
+                    admin_listener.finish_with_termsig().await;
+                }
+            },
+        )
+    };
+    if drain_timeout == 0 {
+        drain.await;
+    } else if time::timeout(Duration::from_secs(drain_timeout), drain)
+        .await
+        .is_err()
+    {
+        // the connections are closed when the runtime shuts down
+        log::warn!(
+            "Closing the connections that are still running queries after {drain_timeout} seconds"
+        );
+    }
 
     // wait for the background services to terminate
     let _ = snapshot_handle.await;
     let _ = bgsave_handle.await;
+    let _ = history_handle.await;
+    if let Some(heartbeat_handle) = heartbeat_handle {
+        let _ = heartbeat_handle.await;
+    }
+    if let Some(health_handle) = health_handle {
+        let _ = health_handle.await;
+    }
+    if let Some(metrics_handle) = metrics_handle {
+        let _ = metrics_handle.await;
+    }
     Ok(db)
 }
 
@@ -149,12 +339,21 @@ fn spawn_task(tx: Sender<bool>, db: Corestore, do_sleep: bool) -> JoinHandle<()>
     })
 }
 
-pub fn finalize_shutdown(corestore: Corestore, pid_file: FileLock) {
+pub fn finalize_shutdown(corestore: Corestore, pid_file: Option<FileLock>) {
     assert_eq!(
         corestore.strong_count(),
         1,
         "Correctness error. finalize_shutdown called before dropping server runtime"
     );
+    let pid_file = match pid_file {
+        Some(pid_file) => pid_file,
+        None => {
+            // ephemeral; there's nothing to save or clean up
+            log::info!("Discarding all data since we're running in ephemeral mode");
+            log::info!("Goodbye :)");
+            return;
+        }
+    };
     let rt = tokio::runtime::Builder::new_multi_thread()
         .thread_name("server-final")
         .enable_all()
@@ -135,6 +135,31 @@ impl AuthProvider {
             err(P::AUTH_ERROR_ALREADYCLAIMED)
         }
     }
+    /// Act as root without a token. This should **only** be used for connections whose origin
+    /// has already been verified by other means (like the local admin socket)
+    pub fn assume_root(&mut self) {
+        self.whoami = Some(USER_ROOT);
+    }
+    /// Act as the given user without a token, if the user exists. This should **only** be used
+    /// for connections whose identity has already been verified by other means (like a verified
+    /// TLS client certificate)
+    pub fn assume_user(&mut self, account: &[u8]) -> bool {
+        let known = self.is_enabled()
+            && account.is_ascii()
+            && account.len() <= AUTHID_SIZE
+            && self.authmap.get(account).is_some();
+        if known {
+            self.whoami = Some(unsafe {
+                // UNSAFE(@ohsayan): We just verified the length
+                AuthID::from_slice(account)
+            });
+        }
+        known
+    }
+    /// Returns the user that is currently logged in, if any
+    pub fn current_user(&self) -> Option<&[u8]> {
+        self.whoami.as_ref().map(|id| id.as_slice())
+    }
     fn are_you_root<P: ProtocolSpec>(&self) -> ActionResult<bool> {
         self.ensure_enabled::<P>()?;
         match self.whoami.as_ref().map(|v| v.eq(&USER_ROOT)) {
@@ -41,11 +41,13 @@ use {
 pub enum Statement {
     /// Create a new space with the provided ID
     CreateSpace(RawSlice),
-    /// Create a new model with the provided configuration
+    /// Create a new model with the provided configuration. A temporary model is only visible to
+    /// (and lives as long as) the connection that created it
     CreateModel {
         entity: Entity,
         model: FieldConfig,
         volatile: bool,
+        temp: bool,
     },
     /// Drop the given model
     DropModel { entity: Entity, force: bool },
@@ -55,10 +57,14 @@ pub enum Statement {
     InspectSpace(Option<RawSlice>),
     /// Inspect the given model
     InspectModel(Option<Entity>),
+    /// Inspect the given model, along with its statistics
+    InspectModelStats(Option<Entity>),
     /// Inspect all the spaces in the database
     InspectSpaces,
     /// Switch to the given entity
     Use(Entity),
+    /// Check the given statement against the current schema without executing it
+    Check(Box<Statement>),
 }
 
 pub type StatementLT<'a> = Life<'a, Statement>;
@@ -238,6 +244,25 @@ impl<'a> Compiler<'a> {
         }
     }
     #[inline(always)]
+    /// Check if the only tokens left are `with stats`, moving the cursor past them if so
+    fn next_with_stats(&mut self) -> bool {
+        fn is_ident(tok: &Token, expected: &[u8]) -> bool {
+            match tok {
+                Token::Identifier(ident) => {
+                    unsafe { ident.as_slice() }.eq_ignore_ascii_case(expected)
+                }
+                _ => false,
+            }
+        }
+        let with_stats = unsafe {
+            self.end_ptr.offset_from(self.cursor) == 2
+                && is_ident(self.deref_cursor(), b"with")
+                && is_ident(&*self.cursor.add(1), b"stats")
+        };
+        unsafe { self.incr_cursor_by(2 * with_stats as usize) };
+        with_stats
+    }
+    #[inline(always)]
     /// Returns the remaining number of tokens
     fn remaining(&self) -> usize {
         self.end_ptr as usize - self.cursor as usize
@@ -272,6 +297,25 @@ impl<'a> Compiler<'a> {
     /// The inner eval method
     fn eval(&mut self, extra_len: usize) -> LangResult<Statement> {
         let stmt = match self.next() {
+            Some(Token::Identifier(check))
+                if unsafe { check.as_slice() }.eq_ignore_ascii_case(b"check") =>
+            {
+                let tok = self.next();
+                self.parse_statement0(tok)
+                    .map(|stmt| Statement::Check(Box::new(stmt)))
+            }
+            tok => self.parse_statement0(tok),
+        };
+        if compiler::likely(self.remaining() == 0 && extra_len == 0) {
+            stmt
+        } else {
+            Err(LangError::InvalidSyntax)
+        }
+    }
+    #[inline(always)]
+    /// Parse a statement starting with the given token
+    fn parse_statement0(&mut self, tok: Option<Token>) -> LangResult<Statement> {
+        match tok {
             Some(tok) => match tok {
                 Token::Keyword(Keyword::Create) => self.parse_create0(),
                 Token::Keyword(Keyword::Drop) => self.parse_drop0(),
@@ -280,11 +324,6 @@ impl<'a> Compiler<'a> {
                 _ => Err(LangError::ExpectedStatement),
             },
             None => Err(LangError::UnexpectedEOF),
-        };
-        if compiler::likely(self.remaining() == 0 && extra_len == 0) {
-            stmt
-        } else {
-            Err(LangError::InvalidSyntax)
         }
     }
     #[inline(always)]
@@ -306,12 +345,20 @@ impl<'a> Compiler<'a> {
         }
     }
     #[inline(always)]
-    /// Parse `inspect model <model>`
+    /// Parse `inspect model <model> [with stats]`
     fn parse_inspect_model0(&mut self) -> LangResult<Statement> {
+        if self.next_with_stats() {
+            return Ok(Statement::InspectModelStats(None));
+        }
         match self.next() {
-            Some(Token::Identifier(ident)) => Ok(Statement::InspectModel(Some(
-                self.parse_entity_name_with_start(ident)?,
-            ))),
+            Some(Token::Identifier(ident)) => {
+                let entity = self.parse_entity_name_with_start(ident)?;
+                if self.next_with_stats() {
+                    Ok(Statement::InspectModelStats(Some(entity)))
+                } else {
+                    Ok(Statement::InspectModel(Some(entity)))
+                }
+            }
             Some(_) => Err(LangError::InvalidSyntax),
             None => Ok(Statement::InspectModel(None)),
         }
@@ -351,6 +398,12 @@ impl<'a> Compiler<'a> {
         match self.next() {
             Some(Token::Keyword(Keyword::Model)) => self.parse_create_model0(),
             Some(Token::Keyword(Keyword::Space)) => self.parse_create_space0(),
+            // `temp` isn't a keyword so that it can still be used as a name
+            Some(Token::Identifier(ident))
+                if unsafe { ident.as_slice() }.eq_ignore_ascii_case(b"temp") =>
+            {
+                self.parse_create_temp_model0()
+            }
             Some(_) => Err(LangError::UnknownCreateQuery),
             None => Err(LangError::UnexpectedEOF),
         }
@@ -362,6 +415,25 @@ impl<'a> Compiler<'a> {
         self.parse_create_model1(entity)
     }
     #[inline(always)]
+    /// Parse a `create temp model` statement. Temporary models are never persisted, so they
+    /// are always volatile
+    fn parse_create_temp_model0(&mut self) -> LangResult<Statement> {
+        if !self.next_eq(&Token::Keyword(Keyword::Model)) {
+            return Err(LangError::InvalidSyntax);
+        }
+        let mut stmt = self.parse_create_model0()?;
+        if let Statement::CreateModel {
+            ref mut volatile,
+            ref mut temp,
+            ..
+        } = stmt
+        {
+            *volatile = true;
+            *temp = true;
+        }
+        Ok(stmt)
+    }
+    #[inline(always)]
     /// Parse a field expression and return a `Statement::CreateModel`
     pub(super) fn parse_create_model1(&mut self, entity: Entity) -> LangResult<Statement> {
         let mut fc = FieldConfig::new();
@@ -400,6 +472,7 @@ impl<'a> Compiler<'a> {
                 entity,
                 model: fc,
                 volatile,
+                temp: false,
             })
         } else {
             Err(LangError::BadExpression)
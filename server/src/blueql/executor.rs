@@ -33,10 +33,17 @@ use {
     },
     crate::{
         actions::{self, ActionError, ActionResult},
-        blueql,
-        corestore::memstore::ObjectID,
+        blueql::{self, Entity},
+        corestore::{
+            memstore::{DdlError, ObjectID, DEFAULT, SYSTEM},
+            table::Table,
+        },
         dbnet::prelude::*,
+        storage::v1::unflush,
+        IoResult,
     },
+    std::{fs, io::ErrorKind},
+    tokio::task,
 };
 
 pub async fn execute<'a, P, C>(
@@ -51,8 +58,22 @@ where
 {
     let statement =
         error::map_ql_err_to_resp::<StatementLT, P>(blueql::compile(maybe_statement, extra))?;
+    // held until the statement is done so that quiescing waits for DDL too
+    let _write = if self::is_ddl(statement.as_ref()) && !registry::is_read_only() {
+        match registry::begin_write() {
+            Some(write) => Some(write),
+            None => return util::err(P::RSTRING_QUIESCED),
+        }
+    } else {
+        None
+    };
     let system_health_okay = registry::state_okay();
     let result = match statement.as_ref() {
+        Statement::Check(statement) => {
+            self::check::<P>(handle, statement)?;
+            con._write_raw(P::RCODE_OKAY).await?;
+            return Ok(());
+        }
         Statement::Use(entity) => handle.swap_entity(entity),
         Statement::CreateSpace(space_name) if system_health_okay => {
             // ret okay
@@ -75,9 +96,11 @@ where
             entity,
             model,
             volatile,
+            temp,
         } if system_health_okay => {
             match model.get_model_code() {
                 // ret okay
+                Ok(code) if *temp => handle.create_temp_table(entity, code),
                 Ok(code) => handle.create_table(entity, code, *volatile),
                 Err(e) => return Err(ActionError::ActionError(error::cold_err::<P>(e))),
             }
@@ -103,6 +126,29 @@ where
                 .await?;
             return Ok(());
         }
+        Statement::InspectModelStats(model) => {
+            // ret directly
+            let (ksid, tblid, table) =
+                actions::translate_ddl_error::<P, _>(handle.resolve_table(model))?;
+            // counting the memory means going over every row, so don't block the runtime
+            match task::spawn_blocking(move || self::model_stats(&ksid, &tblid, &table)).await {
+                Ok(Ok(stats)) => con.write_typed_non_null_array(stats, b'+').await?,
+                Ok(Err(e)) => {
+                    log::error!("Failed to get the size of a model on disk with: {e}");
+                    con._write_raw(P::RCODE_SERVER_ERR).await?;
+                }
+                Err(_) => con._write_raw(P::RCODE_SERVER_ERR).await?,
+            }
+            return Ok(());
+        }
+        _ if registry::is_read_only() => {
+            con._write_raw(P::RSTRING_READ_ONLY).await?;
+            return Ok(());
+        }
+        _ if registry::is_quiesced() => {
+            con._write_raw(P::RSTRING_QUIESCED).await?;
+            return Ok(());
+        }
         _ => {
             // the server is broken
             con._write_raw(P::RCODE_SERVER_ERR).await?;
@@ -113,3 +159,150 @@ where
     con._write_raw(P::RCODE_OKAY).await?;
     Ok(())
 }
+
+/// Returns the description of a model along with its statistics
+fn model_stats(ksid: &ObjectID, tblid: &ObjectID, table: &Table) -> IoResult<Vec<String>> {
+    let disk = match fs::metadata(unflush::table_path(ksid, tblid)) {
+        Ok(metadata) => metadata.len(),
+        // volatile models (and models that haven't been flushed yet) have no data file
+        Err(e) if e.kind() == ErrorKind::NotFound => 0,
+        Err(e) => return Err(e),
+    };
+    Ok(vec![
+        format!("model: {}", table.describe_self()),
+        format!("rows: {}", table.count()),
+        format!("memory: {} bytes (estimated)", table.memory_usage()),
+        format!("disk: {disk} bytes"),
+    ])
+}
+
+/// Returns true if the statement changes the schema
+fn is_ddl(statement: &Statement) -> bool {
+    !matches!(
+        statement,
+        Statement::Use(_)
+            | Statement::InspectSpaces
+            | Statement::InspectSpace(_)
+            | Statement::InspectModel(_)
+            | Statement::InspectModelStats(_)
+    )
+}
+
+/// Check if the given statement would succeed against the current schema without executing it,
+/// returning the error that it would fail with (if any). We can't know if the objects are in use
+/// by other connections, so such conflicts are only detected when the statement is run
+fn check<P: ProtocolSpec>(handle: &Corestore, statement: &Statement) -> ActionResult<()> {
+    if self::is_ddl(statement) && !registry::state_okay() {
+        return if registry::is_read_only() {
+            util::err(P::RSTRING_READ_ONLY)
+        } else if registry::is_quiesced() {
+            util::err(P::RSTRING_QUIESCED)
+        } else {
+            util::err(P::RCODE_SERVER_ERR)
+        };
+    }
+    let result = match statement {
+        // checks can't be nested
+        Statement::Check(_) => return util::err(P::BQL_EXPECTED_STMT),
+        Statement::Use(Entity::Current(ks)) => {
+            match handle.get_keyspace(unsafe { ks.as_slice() }) {
+                Some(_) => Ok(()),
+                None => Err(DdlError::ObjectNotFound),
+            }
+        }
+        Statement::Use(entity) => handle.get_table(entity).map(|_| ()),
+        Statement::CreateSpace(space_name) => {
+            match handle.get_keyspace(unsafe { space_name.as_slice() }) {
+                Some(_) => Err(DdlError::AlreadyExists),
+                None => Ok(()),
+            }
+        }
+        Statement::DropSpace { entity, force } => {
+            let ksid = unsafe { ObjectID::from_slice(entity.as_slice()) };
+            if ksid == SYSTEM || ksid == DEFAULT {
+                Err(DdlError::ProtectedObject)
+            } else {
+                match handle.get_keyspace(&ksid) {
+                    Some(ks) if !*force && ks.table_count() != 0 => Err(DdlError::NotEmpty),
+                    Some(_) => Ok(()),
+                    None => Err(DdlError::ObjectNotFound),
+                }
+            }
+        }
+        Statement::DropModel { entity, force } => {
+            let tblid = match entity {
+                Entity::Current(tbl) | Entity::Full(_, tbl) => unsafe { tbl.as_slice() },
+            };
+            match handle.get_table(entity) {
+                Ok(_) if tblid == DEFAULT.as_slice() => Err(DdlError::ProtectedObject),
+                // dropping a non-empty model without `force` fails like dropping one in use
+                Ok(tbl) if !*force && !tbl.is_empty() => Err(DdlError::StillInUse),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e),
+            }
+        }
+        Statement::CreateModel { entity, model, .. } => {
+            if let Err(e) = model.get_model_code() {
+                return Err(ActionError::ActionError(error::cold_err::<P>(e)));
+            }
+            match handle.get_table(entity) {
+                Ok(_) => Err(DdlError::AlreadyExists),
+                Err(DdlError::ObjectNotFound) => match entity {
+                    Entity::Full(ks, _)
+                        if handle.get_keyspace(unsafe { ks.as_slice() }).is_none() =>
+                    {
+                        Err(DdlError::ObjectNotFound)
+                    }
+                    _ => Ok(()),
+                },
+                Err(e) => Err(e),
+            }
+        }
+        Statement::InspectSpaces => Ok(()),
+        Statement::InspectSpace(space) => {
+            handle.list_tables::<P>(space.as_ref().map(|v| unsafe { v.as_slice() }))?;
+            Ok(())
+        }
+        Statement::InspectModel(model) => {
+            handle.describe_table::<P>(model)?;
+            Ok(())
+        }
+        Statement::InspectModelStats(model) => handle.resolve_table(model).map(|_| ()),
+    };
+    actions::translate_ddl_error::<P, ()>(result)
+}
+
+/// Run a statement from a seed script (see `--init-dir`). There's no connection to write to,
+/// so only the statements that change the schema (and `use`) are accepted
+pub fn execute_seed(handle: &mut Corestore, statement: &[u8]) -> Result<(), String> {
+    let statement = blueql::compile(statement, 0).map_err(|e| format!("{e:?}"))?;
+    let result = match statement.as_ref() {
+        Statement::Use(entity) => handle.swap_entity(entity),
+        Statement::CreateSpace(space_name) => {
+            handle.create_keyspace(unsafe { ObjectID::from_slice(space_name.as_slice()) })
+        }
+        Statement::DropSpace { entity, force } => {
+            let entity = unsafe { ObjectID::from_slice(entity.as_slice()) };
+            if *force {
+                handle.force_drop_keyspace(entity)
+            } else {
+                handle.drop_keyspace(entity)
+            }
+        }
+        Statement::DropModel { entity, force } => handle.drop_table(entity, *force),
+        Statement::CreateModel { temp: true, .. } => {
+            return Err("temporary models can't be created by seed scripts".to_owned())
+        }
+        Statement::CreateModel {
+            entity,
+            model,
+            volatile,
+            ..
+        } => match model.get_model_code() {
+            Ok(code) => handle.create_table(entity, code, *volatile),
+            Err(e) => return Err(format!("{e:?}")),
+        },
+        _ => return Err("only DDL statements and `use` can be used in seed scripts".to_owned()),
+    };
+    result.map_err(|e| format!("{e:?}"))
+}
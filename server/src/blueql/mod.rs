@@ -37,7 +37,11 @@ use {
     self::{ast::Statement, error::LangResult},
     crate::util::Life,
 };
-pub use {ast::Compiler, ast::Entity, executor::execute};
+pub use {
+    ast::Compiler,
+    ast::Entity,
+    executor::{execute, execute_seed},
+};
 
 #[cfg(test)]
 use core::fmt;
@@ -214,6 +214,7 @@ mod ast {
                 names: vec!["username".into(), "password".into(), "posts".into()],
             },
             volatile: true,
+            temp: false,
         };
         (src, stmt)
     }
@@ -239,10 +240,34 @@ mod ast {
                 ],
             },
             volatile: false,
+            temp: false,
         };
         assert_eq!(Compiler::compile(&src).unwrap(), expected);
     }
     #[test]
+    fn stmt_create_temp() {
+        let src = b"create temp model staging(string, binary)".to_vec();
+        let expected = Statement::CreateModel {
+            entity: Entity::Current("staging".into()),
+            model: FieldConfig {
+                names: vec![],
+                types: vec![
+                    TypeExpression(vec![Type::String]),
+                    TypeExpression(vec![Type::Binary]),
+                ],
+            },
+            volatile: true,
+            temp: true,
+        };
+        assert_eq!(Compiler::compile(&src).unwrap(), expected);
+        // `temp` is still a valid name
+        assert!(Compiler::compile(b"create model temp(string, string)").is_ok());
+        assert_eq!(
+            Compiler::compile(b"create temp space staging").unwrap_err(),
+            LangError::InvalidSyntax
+        );
+    }
+    #[test]
     fn stmt_drop_space() {
         assert_eq!(
             Compiler::compile(b"drop space twitter force").unwrap(),
@@ -277,6 +302,44 @@ mod ast {
         );
     }
     #[test]
+    fn stmt_inspect_model_with_stats() {
+        assert_eq!(
+            Compiler::compile(b"inspect model twitter.tweet with stats").unwrap(),
+            Statement::InspectModelStats(Some(Entity::Full("twitter".into(), "tweet".into())))
+        );
+        assert_eq!(
+            Compiler::compile(b"inspect model WITH STATS").unwrap(),
+            Statement::InspectModelStats(None)
+        );
+        // a model called `with` can still be inspected
+        assert_eq!(
+            Compiler::compile(b"inspect model with").unwrap(),
+            Statement::InspectModel(Some(Entity::Current("with".into())))
+        );
+        assert_eq!(
+            Compiler::compile(b"inspect model tweet with").unwrap_err(),
+            LangError::InvalidSyntax
+        );
+    }
+    #[test]
+    fn stmt_check() {
+        assert_eq!(
+            Compiler::compile(b"check drop model twitter.tweet").unwrap(),
+            Statement::Check(Box::new(Statement::DropModel {
+                entity: Entity::Full("twitter".into(), "tweet".into()),
+                force: false
+            }))
+        );
+        assert_eq!(
+            Compiler::compile(b"check check use twitter").unwrap_err(),
+            LangError::InvalidSyntax
+        );
+        assert_eq!(
+            Compiler::compile(b"check").unwrap_err(),
+            LangError::UnexpectedEOF
+        );
+    }
+    #[test]
     fn compile_full() {
         let (src, stmt) = setup_src_stmt();
         assert_eq!(Compiler::compile(&src).unwrap(), stmt)
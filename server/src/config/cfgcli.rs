@@ -93,8 +93,65 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
         Flag::<true>::new(matches.is_present("noart")),
         "--noart"
     );
+    fcli!(
+        server_ephemeral,
+        Flag::<true>::new(matches.is_present("ephemeral")),
+        "--ephemeral"
+    );
+    fcli!(
+        server_readonly,
+        Flag::<true>::new(matches.is_present("readonly")),
+        "--readonly"
+    );
+    fcli!(
+        server_conformance,
+        Flag::<true>::new(matches.is_present("conformance-mode")),
+        "--conformance-mode"
+    );
+    fcli!(server_init_dir, matches.value_of("init-dir"), "--init-dir");
+    fcli!(
+        server_drain_timeout,
+        matches.value_of("drain-timeout"),
+        "--drain-timeout"
+    );
     fcli!(server_mode, matches.value_of("mode"), "--mode");
+    fcli!(
+        server_admin_socket,
+        matches.value_of("admin-socket"),
+        "--admin-socket"
+    );
+    fcli!(
+        server_admin_port,
+        matches.value_of("admin-port"),
+        "--admin-port",
+        Flag::<true>::new(matches.is_present("admin-local")),
+        "--admin-local"
+    );
     fcli!(server_maxcon, matches.value_of("maxcon"), "--maxcon");
+    fcli!(
+        server_max_pipeline,
+        matches.value_of("maxpipeline"),
+        "--maxpipeline"
+    );
+    fcli!(
+        server_query_limits,
+        matches.value_of("maxelements"),
+        "--maxelements",
+        matches.value_of("maxelementsize"),
+        "--maxelementsize",
+        matches.value_of("maxquerysize"),
+        "--maxquerysize"
+    );
+    fcli!(
+        server_health_port,
+        matches.value_of("health-port"),
+        "--health-port"
+    );
+    fcli!(
+        server_metrics_endpoint,
+        matches.value_of("metrics-endpoint"),
+        "--metrics-endpoint"
+    );
     // bgsave settings
     fcli!(
         bgsave_settings,
@@ -111,7 +168,9 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
         matches.value_of("snapkeep"),
         "--snapkeep",
         matches.value_of("stop-write-on-fail"),
-        "--stop-write-on-fail"
+        "--stop-write-on-fail",
+        matches.value_of("snaparchive"),
+        "--snaparchive"
     );
     // TLS settings
     fcli!(
@@ -125,7 +184,11 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
         Flag::<true>::new(matches.is_present("sslonly")),
         "--sslonly",
         matches.value_of("tlspass"),
-        "--tlspassin"
+        "--tlspassin",
+        matches.value_of("tlsclientca"),
+        "--tlsclientca",
+        Flag::<true>::new(matches.is_present("tlsrequireclientcert")),
+        "--tlsrequireclientcert"
     );
     // auth settings
     fcli!(
@@ -133,5 +196,43 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
         matches.value_of("authkey"),
         "--auth-origin-key"
     );
+    // storage settings
+    // --skip-load can be repeated
+    let skip_load = matches
+        .values_of("skip-load")
+        .map(|entries| entries.collect::<Vec<_>>().join(","));
+    fcli!(
+        storage_settings,
+        matches.value_of("readahead"),
+        "--readahead",
+        matches.value_of("memlimit"),
+        "--memlimit",
+        Flag::<true>::new(matches.is_present("force-load")),
+        "--force-load",
+        skip_load.as_deref(),
+        "--skip-load"
+    );
+    // TCP settings
+    fcli!(
+        tcp_settings,
+        Flag::<true>::new(matches.is_present("tcp-nodelay")),
+        "--tcp-nodelay",
+        matches.value_of("tcp-keepalive"),
+        "--tcp-keepalive",
+        matches.value_of("tcp-keepalive-interval"),
+        "--tcp-keepalive-interval",
+        matches.value_of("tcp-sndbuf"),
+        "--tcp-sndbuf",
+        matches.value_of("tcp-rcvbuf"),
+        "--tcp-rcvbuf"
+    );
+    // rate limit settings
+    fcli!(
+        ratelimit_settings,
+        matches.value_of("ratelimit-connection"),
+        "--ratelimit-connection",
+        matches.value_of("ratelimit-user"),
+        "--ratelimit-user"
+    );
     defset
 }
@@ -49,7 +49,27 @@ pub(super) fn parse_env_config() -> Configset {
     // server settings
     fenv!(server_tcp, SKY_SYSTEM_HOST, SKY_SYSTEM_PORT);
     fenv!(server_noart, SKY_SYSTEM_NOART);
+    fenv!(server_ephemeral, SKY_SYSTEM_EPHEMERAL);
+    fenv!(server_admin_socket, SKY_SYSTEM_ADMIN_SOCKET);
+    fenv!(server_readonly, SKY_SYSTEM_READONLY);
+    fenv!(server_conformance, SKY_SYSTEM_CONFORMANCE_MODE);
+    fenv!(server_init_dir, SKY_SYSTEM_INIT_DIR);
+    fenv!(server_drain_timeout, SKY_SYSTEM_DRAIN_TIMEOUT);
+    fenv!(
+        server_admin_port,
+        SKY_SYSTEM_ADMIN_PORT,
+        SKY_SYSTEM_ADMIN_LOCAL
+    );
     fenv!(server_maxcon, SKY_SYSTEM_MAXCON);
+    fenv!(server_max_pipeline, SKY_SYSTEM_MAXPIPELINE);
+    fenv!(
+        server_query_limits,
+        SKY_SYSTEM_MAXELEMENTS,
+        SKY_SYSTEM_MAXELEMENTSIZE,
+        SKY_SYSTEM_MAXQUERYSIZE
+    );
+    fenv!(server_health_port, SKY_SYSTEM_HEALTH_PORT);
+    fenv!(server_metrics_endpoint, SKY_SYSTEM_METRICS_ENDPOINT);
     fenv!(server_mode, SKY_DEPLOY_MODE);
     // bgsave settings
     fenv!(bgsave_settings, SKY_BGSAVE_ENABLED, SKY_BGSAVE_DURATION);
@@ -58,7 +78,8 @@ pub(super) fn parse_env_config() -> Configset {
         snapshot_settings,
         SKY_SNAPSHOT_DURATION,
         SKY_SNAPSHOT_KEEP,
-        SKY_SNAPSHOT_FAILSAFE
+        SKY_SNAPSHOT_FAILSAFE,
+        SKY_SNAPSHOT_ARCHIVE
     );
     // TLS settings
     fenv!(
@@ -67,8 +88,33 @@ pub(super) fn parse_env_config() -> Configset {
         SKY_TLS_CERT,
         SKY_TLS_PORT,
         SKY_TLS_ONLY,
-        SKY_TLS_PASSIN
+        SKY_TLS_PASSIN,
+        SKY_TLS_CLIENT_CA,
+        SKY_TLS_REQUIRE_CLIENT_CERT
     );
     fenv!(auth_settings, SKY_AUTH_ORIGIN_KEY);
+    // storage settings
+    fenv!(
+        storage_settings,
+        SKY_STORAGE_READAHEAD,
+        SKY_STORAGE_MEMLIMIT,
+        SKY_STORAGE_FORCE_LOAD,
+        SKY_STORAGE_SKIP_LOAD
+    );
+    // TCP settings
+    fenv!(
+        tcp_settings,
+        SKY_TCP_NODELAY,
+        SKY_TCP_KEEPALIVE,
+        SKY_TCP_KEEPALIVE_INTERVAL,
+        SKY_TCP_SNDBUF,
+        SKY_TCP_RCVBUF
+    );
+    // rate limit settings
+    fenv!(
+        ratelimit_settings,
+        SKY_RATELIMIT_CONNECTION,
+        SKY_RATELIMIT_USER
+    );
     defset
 }
@@ -30,7 +30,7 @@ use {
         TryFromConfigSource,
     },
     serde::Deserialize,
-    std::net::IpAddr,
+    std::net::{IpAddr, SocketAddr},
 };
 
 /// This struct is an _object representation_ used for parsing the TOML file
@@ -46,6 +46,12 @@ pub struct Config {
     pub(super) ssl: Option<KeySslOpts>,
     /// auth settings
     pub(super) auth: Option<AuthSettings>,
+    /// storage settings
+    pub(super) storage: Option<ConfigKeyStorage>,
+    /// TCP settings
+    pub(super) tcp: Option<ConfigKeyTcp>,
+    /// rate limit settings
+    pub(super) ratelimit: Option<ConfigKeyRateLimit>,
 }
 
 /// This struct represents the `server` key in the TOML file
@@ -60,9 +66,37 @@ pub struct ConfigKeyServer {
     pub(super) noart: Option<bool>,
     /// The maximum number of clients
     pub(super) maxclient: Option<usize>,
+    /// The maximum number of queries in a pipeline
+    pub(super) maxpipeline: Option<usize>,
+    /// The maximum number of elements in a query
+    pub(super) maxelements: Option<usize>,
+    /// The maximum size of an element in a query
+    pub(super) maxelementsize: Option<usize>,
+    /// The maximum size of a query
+    pub(super) maxquerysize: Option<usize>,
     /// The deployment mode
     pub(super) mode: Option<Modeset>,
     pub(super) protocol: Option<ProtocolVersion>,
+    /// The port for the health probe
+    pub(super) health_port: Option<u16>,
+    /// The address for the metrics endpoint
+    pub(super) metrics_endpoint: Option<SocketAddr>,
+    /// Keep all data in memory, without ever touching the disk
+    pub(super) ephemeral: Option<bool>,
+    /// The path to the local admin socket
+    pub(super) admin_socket: Option<String>,
+    /// Reject all writes
+    pub(super) readonly: Option<bool>,
+    /// Run with fixtures and scriptable faults for client conformance suites
+    pub(super) conformance_mode: Option<bool>,
+    /// The directory with the seed scripts for new instances
+    pub(super) init_dir: Option<String>,
+    /// The number of seconds that in-flight queries get to finish on shutdown
+    pub(super) drain_timeout: Option<u64>,
+    /// The port for the admin endpoint
+    pub(super) admin_port: Option<u16>,
+    /// Only listen for admin connections on the loopback interface
+    pub(super) admin_local: Option<bool>,
 }
 
 /// The BGSAVE section in the config file
@@ -90,6 +124,8 @@ pub struct ConfigKeySnapshot {
     pub(super) atmost: usize,
     /// Prevent writes to the database if snapshotting fails
     pub(super) failsafe: Option<bool>,
+    /// Move older snapshots into this directory instead of deleting them
+    pub(super) archive: Option<String>,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq)]
@@ -99,6 +135,45 @@ pub struct KeySslOpts {
     pub(super) port: u16,
     pub(super) only: Option<bool>,
     pub(super) passin: Option<String>,
+    pub(super) clientca: Option<String>,
+    pub(super) require_client_cert: Option<bool>,
+}
+
+/// The storage section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyStorage {
+    /// The number of table files to read ahead of time when restoring data
+    pub(super) readahead: Option<usize>,
+    /// The maximum memory (in MiB) that the data is allowed to use
+    pub(super) memlimit: Option<u64>,
+    /// Load the data even if it is estimated to exceed `memlimit`
+    pub(super) force_load: Option<bool>,
+    /// The spaces and models whose data shouldn't be loaded
+    pub(super) skip_load: Option<Vec<String>>,
+}
+
+/// The TCP section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyTcp {
+    /// Disable Nagle's algorithm
+    pub(super) nodelay: Option<bool>,
+    /// The idle time (in seconds) before keepalive probes are sent
+    pub(super) keepalive: Option<u64>,
+    /// The time (in seconds) between keepalive probes
+    pub(super) keepalive_interval: Option<u64>,
+    /// The size of the send buffer (in bytes)
+    pub(super) send_buffer: Option<usize>,
+    /// The size of the receive buffer (in bytes)
+    pub(super) recv_buffer: Option<usize>,
+}
+
+/// The rate limit section in the TOML file
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct ConfigKeyRateLimit {
+    /// The maximum number of queries per second on a single connection
+    pub(super) connection: Option<u64>,
+    /// The maximum number of queries per second for all the connections of a user
+    pub(super) user: Option<u64>,
 }
 
 /// A custom non-null type for config files
@@ -171,6 +246,9 @@ pub fn from_file(file: ConfigFile) -> Configset {
         snapshot,
         ssl,
         auth,
+        storage,
+        tcp,
+        ratelimit,
     } = file;
     // server settings
     set.server_tcp(
@@ -181,8 +259,37 @@ pub fn from_file(file: ConfigFile) -> Configset {
     );
     set.protocol_settings(server.protocol, "server.protocol");
     set.server_maxcon(Optional::from(server.maxclient), "server.maxcon");
+    set.server_max_pipeline(Optional::from(server.maxpipeline), "server.maxpipeline");
+    set.server_query_limits(
+        Optional::from(server.maxelements),
+        "server.maxelements",
+        Optional::from(server.maxelementsize),
+        "server.maxelementsize",
+        Optional::from(server.maxquerysize),
+        "server.maxquerysize",
+    );
     set.server_noart(Optional::from(server.noart), "server.noart");
     set.server_mode(Optional::from(server.mode), "server.mode");
+    set.server_health_port(Optional::from(server.health_port), "server.health_port");
+    set.server_metrics_endpoint(
+        Optional::from(server.metrics_endpoint),
+        "server.metrics_endpoint",
+    );
+    set.server_ephemeral(Optional::from(server.ephemeral), "server.ephemeral");
+    set.server_admin_socket(OptString::from(server.admin_socket), "server.admin_socket");
+    set.server_readonly(Optional::from(server.readonly), "server.readonly");
+    set.server_conformance(
+        Optional::from(server.conformance_mode),
+        "server.conformance_mode",
+    );
+    set.server_init_dir(OptString::from(server.init_dir), "server.init_dir");
+    set.server_drain_timeout(Optional::from(server.drain_timeout), "server.drain_timeout");
+    set.server_admin_port(
+        Optional::from(server.admin_port),
+        "server.admin_port",
+        Optional::from(server.admin_local),
+        "server.admin_local",
+    );
     // bgsave settings
     if let Some(bgsave) = bgsave {
         let ConfigKeyBGSAVE { enabled, every } = bgsave;
@@ -199,6 +306,7 @@ pub fn from_file(file: ConfigFile) -> Configset {
             every,
             atmost,
             failsafe,
+            archive,
         } = snapshot;
         set.snapshot_settings(
             NonNull::from(every),
@@ -207,6 +315,8 @@ pub fn from_file(file: ConfigFile) -> Configset {
             "snapshot.atmost",
             Optional::from(failsafe),
             "snapshot.failsafe",
+            OptString::from(archive),
+            "snapshot.archive",
         );
     }
     // TLS settings
@@ -217,6 +327,8 @@ pub fn from_file(file: ConfigFile) -> Configset {
             port,
             only,
             passin,
+            clientca,
+            require_client_cert,
         } = tls;
         set.tls_settings(
             NonNull::from(key),
@@ -229,11 +341,66 @@ pub fn from_file(file: ConfigFile) -> Configset {
             "ssl.only",
             OptString::from(passin),
             "ssl.passin",
+            OptString::from(clientca),
+            "ssl.clientca",
+            Optional::from(require_client_cert),
+            "ssl.require_client_cert",
         );
     }
     if let Some(auth) = auth {
         let AuthSettings { origin_key } = auth;
         set.auth_settings(Optional::from(origin_key), "auth.origin")
     }
+    if let Some(storage) = storage {
+        let ConfigKeyStorage {
+            readahead,
+            memlimit,
+            force_load,
+            skip_load,
+        } = storage;
+        let skip_load = skip_load
+            .filter(|entries| !entries.is_empty())
+            .map(|entries| entries.join(","));
+        set.storage_settings(
+            Optional::from(readahead),
+            "storage.readahead",
+            Optional::from(memlimit),
+            "storage.memlimit",
+            Optional::from(force_load),
+            "storage.force_load",
+            skip_load.as_deref(),
+            "storage.skip_load",
+        );
+    }
+    if let Some(tcp) = tcp {
+        let ConfigKeyTcp {
+            nodelay,
+            keepalive,
+            keepalive_interval,
+            send_buffer,
+            recv_buffer,
+        } = tcp;
+        set.tcp_settings(
+            Optional::from(nodelay),
+            "tcp.nodelay",
+            Optional::from(keepalive),
+            "tcp.keepalive",
+            Optional::from(keepalive_interval),
+            "tcp.keepalive_interval",
+            Optional::from(send_buffer),
+            "tcp.send_buffer",
+            Optional::from(recv_buffer),
+            "tcp.recv_buffer",
+        );
+    }
+    if let Some(ratelimit) = ratelimit {
+        let ConfigKeyRateLimit { connection, user } = ratelimit;
+        set.ratelimit_settings(
+            Optional::from(connection),
+            "ratelimit.connection",
+            Optional::from(user),
+            "ratelimit.user",
+        );
+    }
     set
 }
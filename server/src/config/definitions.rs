@@ -25,14 +25,14 @@
 */
 
 use {
-    super::{feedback::WarningStack, DEFAULT_IPV4, DEFAULT_PORT},
+    super::{feedback::WarningStack, DEFAULT_IPV4, DEFAULT_PORT, DEFAULT_READAHEAD},
     crate::{config::AuthkeyWrapper, dbnet::MAXIMUM_CONNECTION_LIMIT},
     core::{fmt, str::FromStr},
     serde::{
         de::{self, Deserializer, Visitor},
         Deserialize,
     },
-    std::net::IpAddr,
+    std::net::{IpAddr, SocketAddr},
 };
 
 /// The BGSAVE configuration
@@ -69,7 +69,7 @@ impl BGSave {
 }
 
 #[repr(u8)]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum ProtocolVersion {
     V1,
     V2,
@@ -131,12 +131,45 @@ pub struct ConfigurationSet {
     pub ports: PortConfig,
     /// The maximum number of connections
     pub maxcon: usize,
+    /// The maximum number of queries in a pipeline. `0` indicates that there is no limit
+    pub max_pipeline: usize,
     /// The deployment mode
     pub mode: Modeset,
     /// The auth settings
     pub auth: AuthSettings,
     /// The protocol version
     pub protocol: ProtocolVersion,
+    /// The storage settings
+    pub storage: StorageSettings,
+    /// The port for the health probe. `0` indicates that the health probe is disabled
+    pub health_port: u16,
+    /// If `ephemeral` is set to true, all data is kept in memory and nothing is ever written to
+    /// (or read from) disk
+    pub ephemeral: bool,
+    /// The path to the local admin socket, if enabled
+    pub admin_socket: Option<String>,
+    /// If `readonly` is set to true, all writes (including DDL) are rejected
+    pub readonly: bool,
+    /// The port for the admin endpoint. `0` indicates that the admin endpoint is disabled
+    pub admin_port: u16,
+    /// If `admin_local` is set to true, the admin endpoint only listens on the loopback interface
+    pub admin_local: bool,
+    /// The TCP socket options for accepted connections
+    pub tcp: TcpSettings,
+    /// The query rate limits
+    pub ratelimit: RateLimit,
+    /// The limits on the size of queries
+    pub limits: QueryLimits,
+    /// The address for the metrics endpoint, if enabled
+    pub metrics_endpoint: Option<SocketAddr>,
+    /// If `conformance` is set to true, the server runs ephemerally with a fixed set of fixtures
+    /// and lets clients script faults (for client library conformance suites)
+    pub conformance: bool,
+    /// The directory with the BlueQL seed scripts that are run when a new instance is initialized
+    pub init_dir: Option<String>,
+    /// The number of seconds that in-flight queries get to finish on shutdown. `0` indicates that
+    /// we wait for all connections to finish
+    pub drain_timeout: u64,
 }
 
 impl ConfigurationSet {
@@ -147,9 +180,24 @@ impl ConfigurationSet {
         snapshot: SnapshotConfig,
         ports: PortConfig,
         maxcon: usize,
+        max_pipeline: usize,
         mode: Modeset,
         auth: AuthSettings,
         protocol: ProtocolVersion,
+        storage: StorageSettings,
+        health_port: u16,
+        ephemeral: bool,
+        admin_socket: Option<String>,
+        readonly: bool,
+        admin_port: u16,
+        admin_local: bool,
+        tcp: TcpSettings,
+        ratelimit: RateLimit,
+        limits: QueryLimits,
+        metrics_endpoint: Option<SocketAddr>,
+        conformance: bool,
+        init_dir: Option<String>,
+        drain_timeout: u64,
     ) -> Self {
         Self {
             noart,
@@ -157,9 +205,24 @@ impl ConfigurationSet {
             snapshot,
             ports,
             maxcon,
+            max_pipeline,
             mode,
             auth,
             protocol,
+            storage,
+            health_port,
+            ephemeral,
+            admin_socket,
+            readonly,
+            admin_port,
+            admin_local,
+            tcp,
+            ratelimit,
+            limits,
+            metrics_endpoint,
+            conformance,
+            init_dir,
+            drain_timeout,
         }
     }
     /// Create a default `ConfigurationSet` with the following setup defaults:
@@ -176,9 +239,24 @@ impl ConfigurationSet {
             SnapshotConfig::default(),
             PortConfig::new_insecure_only(DEFAULT_IPV4, 2003),
             MAXIMUM_CONNECTION_LIMIT,
+            0,
             Modeset::Dev,
             AuthSettings::default(),
             ProtocolVersion::V2,
+            StorageSettings::default(),
+            0,
+            false,
+            None,
+            false,
+            0,
+            false,
+            TcpSettings::default(),
+            RateLimit::default(),
+            QueryLimits::default(),
+            None,
+            false,
+            None,
+            0,
         )
     }
     /// Returns `false` if `noart` is enabled. Otherwise it returns `true`
@@ -279,15 +357,29 @@ pub struct SslOpts {
     pub chain: String,
     pub port: u16,
     pub passfile: Option<String>,
+    /// The CA certificates used to verify client certificates. If set, clients that present a
+    /// certificate are logged in as the user named by it (if the user exists)
+    pub clientca: Option<String>,
+    /// Refuse clients that don't present a certificate (instead of letting them `AUTH LOGIN`)
+    pub require_client_cert: bool,
 }
 
 impl SslOpts {
-    pub const fn new(key: String, chain: String, port: u16, passfile: Option<String>) -> Self {
+    pub const fn new(
+        key: String,
+        chain: String,
+        port: u16,
+        passfile: Option<String>,
+        clientca: Option<String>,
+        require_client_cert: bool,
+    ) -> Self {
         SslOpts {
             key,
             chain,
             port,
             passfile,
+            clientca,
+            require_client_cert,
         }
     }
     pub const fn get_port(&self) -> u16 {
@@ -305,19 +397,22 @@ pub struct SnapshotPref {
     pub atmost: usize,
     /// Lock writes if snapshotting fails
     pub poison: bool,
+    /// The directory into which older snapshots are moved (instead of being deleted)
+    pub archive: Option<String>,
 }
 
 impl SnapshotPref {
     /// Create a new a new `SnapshotPref` instance
-    pub const fn new(every: u64, atmost: usize, poison: bool) -> Self {
+    pub const fn new(every: u64, atmost: usize, poison: bool, archive: Option<String>) -> Self {
         SnapshotPref {
             every,
             atmost,
             poison,
+            archive,
         }
     }
     /// Returns `every,almost` as a tuple for pattern matching
-    pub const fn decompose(self) -> (u64, usize, bool) {
+    pub fn decompose(self) -> (u64, usize, bool) {
         (self.every, self.atmost, self.poison)
     }
 }
@@ -346,6 +441,47 @@ impl SnapshotConfig {
 
 type RestoreFile = Option<String>;
 
+#[derive(Debug, PartialEq, Eq)]
+/// A task that is run (instead of starting the server) before `skyd` exits
+pub enum Task {
+    /// Print and check the manifest of the backup in the given directory
+    BackupInfo(String),
+    /// Run the self-test
+    Selftest,
+    /// Import a file into a model
+    Import(ImportTask),
+    /// Export a model into a file
+    Export(ExportTask),
+    /// Print the state of the lock on the data directory
+    LockStatus,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The options for `skyd import`
+pub struct ImportTask {
+    /// The file to import
+    pub file: String,
+    /// The model to import into, as `space.model`
+    pub model: String,
+    /// The format of the file. If not set, it is picked using the file extension
+    pub format: Option<String>,
+    /// The first row of a CSV file is a header
+    pub header: bool,
+    /// Only validate the rows, without writing anything
+    pub dry_run: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// The options for `skyd export`
+pub struct ExportTask {
+    /// The model to export, as `space.model`
+    pub model: String,
+    /// The format of the output. If not set, it is picked using the extension of `out`
+    pub format: Option<String>,
+    /// The file to write to. If not set, the rows are written to `stdout`
+    pub out: Option<String>,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 /// The type of configuration:
 /// - The default configuration
@@ -353,6 +489,7 @@ type RestoreFile = Option<String>;
 pub struct ConfigType {
     pub(super) config: ConfigurationSet,
     restore: RestoreFile,
+    task: Option<Task>,
     is_custom: bool,
     warnings: Option<WarningStack>,
 }
@@ -367,6 +504,7 @@ impl ConfigType {
         Self {
             config,
             restore,
+            task: None,
             is_custom,
             warnings,
         }
@@ -376,6 +514,18 @@ impl ConfigType {
             warnings.print_warnings()
         }
     }
+    /// Set the task that should be run instead of starting the server
+    pub fn with_task(mut self, task: Option<Task>) -> Self {
+        self.task = task;
+        self
+    }
+    pub fn task(&self) -> Option<&Task> {
+        self.task.as_ref()
+    }
+    /// The storage settings, for the tasks that read the data directory
+    pub fn storage(&self) -> &StorageSettings {
+        &self.config.storage
+    }
     pub fn finish(self) -> (ConfigurationSet, Option<String>) {
         (self.config, self.restore)
     }
@@ -458,6 +608,215 @@ impl<'de> Deserialize<'de> for Modeset {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+/// The storage engine settings
+pub struct StorageSettings {
+    /// The number of table files to read ahead of time when restoring data
+    pub readahead: usize,
+    /// The maximum memory (in MiB) that the data is allowed to use. `0` indicates that there is
+    /// no limit
+    pub memlimit: u64,
+    /// Load the data even if it is estimated to exceed `memlimit`
+    pub force_load: bool,
+    /// The spaces and models whose data shouldn't be loaded
+    pub skip_load: SkipLoadSet,
+}
+
+impl StorageSettings {
+    pub const fn new(
+        readahead: usize,
+        memlimit: u64,
+        force_load: bool,
+        skip_load: SkipLoadSet,
+    ) -> Self {
+        Self {
+            readahead,
+            memlimit,
+            force_load,
+            skip_load,
+        }
+    }
+    /// The default storage settings
+    ///
+    /// Defaults:
+    /// - `readahead`: 2
+    /// - `memlimit`: 0 (no limit)
+    /// - `force_load`: false
+    /// - `skip_load`: nothing is skipped
+    pub const fn default() -> Self {
+        Self::new(DEFAULT_READAHEAD, 0, false, SkipLoadSet::new_empty())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The TCP socket options that are set on every accepted connection
+pub struct TcpSettings {
+    /// Disable Nagle's algorithm (`TCP_NODELAY`)
+    pub nodelay: bool,
+    /// The time (in seconds) a connection has to be idle before keepalive probes are sent. `0`
+    /// indicates that keepalive is disabled
+    pub keepalive: u64,
+    /// The time (in seconds) between keepalive probes. `0` uses the OS default
+    pub keepalive_interval: u64,
+    /// The size (in bytes) of the send buffer. `0` uses the OS default
+    pub send_buffer: usize,
+    /// The size (in bytes) of the receive buffer. `0` uses the OS default
+    pub recv_buffer: usize,
+}
+
+impl TcpSettings {
+    pub const fn new(
+        nodelay: bool,
+        keepalive: u64,
+        keepalive_interval: u64,
+        send_buffer: usize,
+        recv_buffer: usize,
+    ) -> Self {
+        Self {
+            nodelay,
+            keepalive,
+            keepalive_interval,
+            send_buffer,
+            recv_buffer,
+        }
+    }
+    /// The default TCP settings, which leave all the socket options as the OS sets them
+    ///
+    /// Defaults:
+    /// - `nodelay`: false
+    /// - `keepalive`: 0 (disabled)
+    /// - `keepalive_interval`: 0 (OS default)
+    /// - `send_buffer`: 0 (OS default)
+    /// - `recv_buffer`: 0 (OS default)
+    pub const fn default() -> Self {
+        Self::new(false, 0, 0, 0, 0)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The maximum number of queries that can be run per second. Queries in a pipeline are counted
+/// individually
+pub struct RateLimit {
+    /// The limit for a single connection. `0` indicates that there is no limit
+    pub connection: u64,
+    /// The limit for all the connections of a user. `0` indicates that there is no limit
+    pub user: u64,
+}
+
+impl RateLimit {
+    pub const fn new(connection: u64, user: u64) -> Self {
+        Self { connection, user }
+    }
+    /// The default rate limits, which don't limit anything
+    ///
+    /// Defaults:
+    /// - `connection`: 0 (no limit)
+    /// - `user`: 0 (no limit)
+    pub const fn default() -> Self {
+        Self::new(0, 0)
+    }
+    /// Returns true if any of the limits are set
+    pub const fn is_enabled(&self) -> bool {
+        self.connection != 0 || self.user != 0
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The limits on the size of queries. Queries exceeding them are rejected while they're being
+/// parsed (so that hostile clients can't exhaust our memory)
+pub struct QueryLimits {
+    /// The maximum number of elements in a query. `0` indicates that there is no limit
+    pub elements: usize,
+    /// The maximum size (in bytes) of an element. `0` indicates that there is no limit
+    pub element_size: usize,
+    /// The maximum size (in bytes) of a query. `0` indicates that there is no limit
+    pub query_size: usize,
+}
+
+impl QueryLimits {
+    pub const fn new(elements: usize, element_size: usize, query_size: usize) -> Self {
+        Self {
+            elements,
+            element_size,
+            query_size,
+        }
+    }
+    /// The default query limits, which don't limit anything
+    ///
+    /// Defaults:
+    /// - `elements`: 0 (no limit)
+    /// - `element_size`: 0 (no limit)
+    /// - `query_size`: 0 (no limit)
+    pub const fn default() -> Self {
+        Self::new(0, 0, 0)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// A set of spaces (`space`) and models (`space.model`) whose data shouldn't be loaded on
+/// startup
+pub struct SkipLoadSet {
+    entries: Vec<(String, Option<String>)>,
+}
+
+impl SkipLoadSet {
+    pub const fn new_empty() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Returns true if the given model is to be skipped (either by itself or because its space is)
+    pub fn skips_model(&self, space: &str, model: &str) -> bool {
+        self.entries.iter().any(|(skip_space, skip_model)| {
+            skip_space == space && skip_model.as_deref().map_or(true, |m| m == model)
+        })
+    }
+    /// Returns true if the whole space is to be skipped
+    pub fn skips_space(&self, space: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|(skip_space, skip_model)| skip_space == space && skip_model.is_none())
+    }
+    /// Returns an iterator over the `(space, model)` entries
+    pub fn entries(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.entries
+            .iter()
+            .map(|(space, model)| (space.as_str(), model.as_deref()))
+    }
+}
+
+impl FromStr for SkipLoadSet {
+    type Err = ();
+    fn from_str(st: &str) -> Result<Self, Self::Err> {
+        fn is_valid_name(name: &str) -> bool {
+            // same rules as for identifiers in BlueQL
+            let mut chars = name.chars();
+            name.len() <= 64
+                && chars
+                    .next()
+                    .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        let mut entries = Vec::new();
+        for entry in st.split(',').map(str::trim) {
+            let (space, model) = match entry.split_once('.') {
+                Some((space, model)) if is_valid_name(model) => (space, Some(model)),
+                Some(_) => return Err(()),
+                None => (entry, None),
+            };
+            // the system space holds the auth data and can never be skipped
+            if !is_valid_name(space) || space == "system" {
+                return Err(());
+            }
+            entries.push((space.to_owned(), model.map(str::to_owned)));
+        }
+        Ok(Self { entries })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Deserialize)]
 pub struct AuthSettings {
     pub origin_key: Option<AuthkeyWrapper>,
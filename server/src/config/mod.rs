@@ -26,12 +26,12 @@
 
 use {
     crate::auth::provider::Authkey,
-    clap::{load_yaml, App},
+    clap::{load_yaml, App, ArgMatches},
     core::str::FromStr,
     std::{
         env::VarError,
         fs,
-        net::{IpAddr, Ipv4Addr},
+        net::{IpAddr, Ipv4Addr, SocketAddr},
     },
 };
 
@@ -59,6 +59,8 @@ const DEFAULT_BGSAVE_DURATION: u64 = 120;
 const DEFAULT_SNAPSHOT_FAILSAFE: bool = true;
 // TLS defaults
 const DEFAULT_SSL_PORT: u16 = 2004;
+// storage defaults
+const DEFAULT_READAHEAD: usize = 2;
 
 type StaticStr = &'static str;
 
@@ -449,6 +451,83 @@ impl Configset {
         self.try_mutate(nart, &mut noart, nart_key, "true/false");
         self.cfg.noart = noart;
     }
+    pub fn server_ephemeral(
+        &mut self,
+        nephemeral: impl TryFromConfigSource<bool>,
+        nephemeral_key: StaticStr,
+    ) {
+        let mut ephemeral = false;
+        self.try_mutate(nephemeral, &mut ephemeral, nephemeral_key, "true/false");
+        self.cfg.ephemeral = ephemeral;
+    }
+    pub fn server_readonly(
+        &mut self,
+        nreadonly: impl TryFromConfigSource<bool>,
+        nreadonly_key: StaticStr,
+    ) {
+        let mut readonly = false;
+        self.try_mutate(nreadonly, &mut readonly, nreadonly_key, "true/false");
+        self.cfg.readonly = readonly;
+    }
+    pub fn server_conformance(
+        &mut self,
+        nconformance: impl TryFromConfigSource<bool>,
+        nconformance_key: StaticStr,
+    ) {
+        let mut conformance = false;
+        self.try_mutate(
+            nconformance,
+            &mut conformance,
+            nconformance_key,
+            "true/false",
+        );
+        self.cfg.conformance = conformance;
+        // conformance mode never touches the data directory
+        self.cfg.ephemeral |= conformance;
+    }
+    pub fn server_init_dir(
+        &mut self,
+        npath: impl TryFromConfigSource<OptString>,
+        npath_key: StaticStr,
+    ) {
+        let mut path = OptString::new_null();
+        self.try_mutate(
+            npath,
+            &mut path,
+            npath_key,
+            "path to the directory with the seed scripts",
+        );
+        self.cfg.init_dir = path.base;
+    }
+    pub fn server_drain_timeout(
+        &mut self,
+        ntimeout: impl TryFromConfigSource<u64>,
+        ntimeout_key: StaticStr,
+    ) {
+        let mut drain_timeout = 0;
+        self.try_mutate(
+            ntimeout,
+            &mut drain_timeout,
+            ntimeout_key,
+            "a positive integer (in seconds). 0 indicates that there is no deadline",
+        );
+        self.cfg.drain_timeout = drain_timeout;
+    }
+    pub fn server_admin_socket(
+        &mut self,
+        npath: impl TryFromConfigSource<OptString>,
+        npath_key: StaticStr,
+    ) {
+        let mut path = OptString::new_null();
+        self.try_mutate(npath, &mut path, npath_key, "path to the admin socket");
+        if path.base.is_some() && !cfg!(unix) {
+            self.wstack.push(format!(
+                "`{npath_key}` is only supported on Unix-based systems and will be ignored"
+            ));
+            path = OptString::new_null();
+        }
+        self.cfg.admin_socket = path.base;
+    }
     pub fn server_maxcon(
         &mut self,
         nmaxcon: impl TryFromConfigSource<usize>,
@@ -464,6 +543,105 @@ impl Configset {
         );
         self.cfg.maxcon = maxcon;
     }
+    pub fn server_max_pipeline(
+        &mut self,
+        nmax: impl TryFromConfigSource<usize>,
+        nmax_key: StaticStr,
+    ) {
+        let mut max_pipeline = 0;
+        self.try_mutate(
+            nmax,
+            &mut max_pipeline,
+            nmax_key,
+            "a positive integer. 0 indicates that there is no limit",
+        );
+        self.cfg.max_pipeline = max_pipeline;
+    }
+    pub fn server_query_limits(
+        &mut self,
+        nelements: impl TryFromConfigSource<usize>,
+        nelements_key: StaticStr,
+        nelement_size: impl TryFromConfigSource<usize>,
+        nelement_size_key: StaticStr,
+        nquery_size: impl TryFromConfigSource<usize>,
+        nquery_size_key: StaticStr,
+    ) {
+        let mut limits = QueryLimits::default();
+        self.try_mutate(
+            nelements,
+            &mut limits.elements,
+            nelements_key,
+            "a positive integer. 0 indicates that there is no limit",
+        );
+        self.try_mutate(
+            nelement_size,
+            &mut limits.element_size,
+            nelement_size_key,
+            "a positive integer (in bytes). 0 indicates that there is no limit",
+        );
+        self.try_mutate(
+            nquery_size,
+            &mut limits.query_size,
+            nquery_size_key,
+            "a positive integer (in bytes). 0 indicates that there is no limit",
+        );
+        self.cfg.limits = limits;
+    }
+    pub fn server_health_port(
+        &mut self,
+        nport: impl TryFromConfigSource<u16>,
+        nport_key: StaticStr,
+    ) {
+        let mut port = 0;
+        self.try_mutate(
+            nport,
+            &mut port,
+            nport_key,
+            "a 16-bit positive integer. 0 disables the health probe",
+        );
+        self.cfg.health_port = port;
+    }
+    pub fn server_metrics_endpoint(
+        &mut self,
+        nendpoint: impl TryFromConfigSource<SocketAddr>,
+        nendpoint_key: StaticStr,
+    ) {
+        match nendpoint.try_parse() {
+            ConfigSourceParseResult::Okay(endpoint) => {
+                self.mutated();
+                self.cfg.metrics_endpoint = Some(endpoint);
+            }
+            ConfigSourceParseResult::ParseFailure => {
+                self.mutated();
+                self.epush(nendpoint_key, "a socket address (like `127.0.0.1:2006`)");
+            }
+            ConfigSourceParseResult::Absent => {}
+        }
+    }
+    pub fn server_admin_port(
+        &mut self,
+        nport: impl TryFromConfigSource<u16>,
+        nport_key: StaticStr,
+        nlocal: impl TryFromConfigSource<bool>,
+        nlocal_key: StaticStr,
+    ) {
+        let mut port = 0;
+        let mut local = false;
+        self.try_mutate(
+            nport,
+            &mut port,
+            nport_key,
+            "a 16-bit positive integer. 0 disables the admin endpoint",
+        );
+        self.try_mutate(nlocal, &mut local, nlocal_key, "true/false");
+        if local && port == 0 {
+            self.wstack.push(format!(
+                "`{nlocal_key}` has no effect since the admin endpoint is disabled"
+            ));
+        }
+        self.cfg.admin_port = port;
+        self.cfg.admin_local = local;
+    }
     pub fn server_mode(&mut self, nmode: impl TryFromConfigSource<Modeset>, nmode_key: StaticStr) {
         let mut modeset = Modeset::Dev;
         self.try_mutate(
@@ -520,6 +698,8 @@ impl Configset {
         natmost_key: StaticStr,
         nfailsafe: impl TryFromConfigSource<bool>,
         nfailsafe_key: StaticStr,
+        narchive: impl TryFromConfigSource<OptString>,
+        narchive_key: StaticStr,
     ) {
         match (nevery.is_present(), natmost.is_present()) {
             (false, false) => {
@@ -533,6 +713,12 @@ impl Configset {
                         "Specifying `{nfailsafe_key}` is usless when snapshots are disabled"
                     ));
                 }
+                if narchive.is_present() {
+                    self.mutated();
+                    self.wstack.push(format!(
+                        "Specifying `{narchive_key}` is pointless when snapshots are disabled"
+                    ));
+                }
             }
             (true, true) => {
                 let mut every = 0;
@@ -552,8 +738,19 @@ impl Configset {
                     "a positive integer. 0 indicates that all snapshots will be kept",
                 );
                 self.try_mutate(nfailsafe, &mut failsafe, nfailsafe_key, "true/false");
-                self.cfg.snapshot =
-                    SnapshotConfig::Enabled(SnapshotPref::new(every, atmost, failsafe));
+                let mut archive = OptString::new_null();
+                self.try_mutate(
+                    narchive,
+                    &mut archive,
+                    narchive_key,
+                    "path to the snapshot archive directory",
+                );
+                self.cfg.snapshot = SnapshotConfig::Enabled(SnapshotPref::new(
+                    every,
+                    atmost,
+                    failsafe,
+                    archive.base,
+                ));
             }
             (false, true) | (true, false) => {
                 // no changes, but still attempted to change
@@ -581,6 +778,10 @@ impl Configset {
         nonly_key: StaticStr,
         npass: impl TryFromConfigSource<OptString>,
         npass_key: StaticStr,
+        nclientca: impl TryFromConfigSource<OptString>,
+        nclientca_key: StaticStr,
+        nreqcert: impl TryFromConfigSource<bool>,
+        nreqcert_key: StaticStr,
     ) {
         match (nkey.is_present(), ncert.is_present()) {
             (true, true) => {
@@ -607,7 +808,37 @@ impl Configset {
                     "path to TLS cert passphrase",
                 );
 
-                let sslopts = SslOpts::new(key, cert, port, tls_pass.base);
+                // check if client certificates are to be verified
+                let mut clientca = OptString::new_null();
+                self.try_mutate(
+                    nclientca,
+                    &mut clientca,
+                    nclientca_key,
+                    "path to the CA certificates for client certificates",
+                );
+
+                // check if clients without a certificate are to be refused
+                let mut require_client_cert = false;
+                self.try_mutate(
+                    nreqcert,
+                    &mut require_client_cert,
+                    nreqcert_key,
+                    "true/false",
+                );
+                if require_client_cert && clientca.base.is_none() {
+                    self.wstack.push(format!(
+                        "Specifying `{nreqcert_key}` is pointless without `{nclientca_key}`"
+                    ));
+                }
+
+                let sslopts = SslOpts::new(
+                    key,
+                    cert,
+                    port,
+                    tls_pass.base,
+                    clientca.base,
+                    require_client_cert,
+                );
                 // now check if TLS only
                 if tls_only {
                     let host = self.cfg.ports.get_host();
@@ -642,6 +873,18 @@ impl Configset {
                         "Specifying `{npass_key}` is pointless when TLS is disabled"
                     ));
                 }
+                if nclientca.is_present() {
+                    self.mutated();
+                    self.wstack.push(format!(
+                        "Specifying `{nclientca_key}` is pointless when TLS is disabled"
+                    ));
+                }
+                if nreqcert.is_present() {
+                    self.mutated();
+                    self.wstack.push(format!(
+                        "Specifying `{nreqcert_key}` is pointless when TLS is disabled"
+                    ));
+                }
             }
         }
     }
@@ -664,11 +907,135 @@ impl Configset {
     }
 }
 
+// Storage settings
+#[allow(clippy::too_many_arguments)]
+impl Configset {
+    pub fn storage_settings(
+        &mut self,
+        nreadahead: impl TryFromConfigSource<usize>,
+        nreadahead_key: StaticStr,
+        nmemlimit: impl TryFromConfigSource<u64>,
+        nmemlimit_key: StaticStr,
+        nforce: impl TryFromConfigSource<bool>,
+        nforce_key: StaticStr,
+        nskip: impl TryFromConfigSource<SkipLoadSet>,
+        nskip_key: StaticStr,
+    ) {
+        let mut readahead = DEFAULT_READAHEAD;
+        let mut memlimit = 0;
+        let mut force_load = false;
+        let mut skip_load = SkipLoadSet::new_empty();
+        self.try_mutate(
+            nreadahead,
+            &mut readahead,
+            nreadahead_key,
+            "a positive integer. 0 disables read-ahead",
+        );
+        self.try_mutate(
+            nmemlimit,
+            &mut memlimit,
+            nmemlimit_key,
+            "a positive integer (in MiB). 0 indicates that there is no limit",
+        );
+        self.try_mutate(nforce, &mut force_load, nforce_key, "true/false");
+        if force_load && memlimit == 0 {
+            self.wstack.push(format!(
+                "Specifying `{nforce_key}` is pointless when no memory limit is set"
+            ));
+        }
+        self.try_mutate(
+            nskip,
+            &mut skip_load,
+            nskip_key,
+            "a comma separated list of `space` or `space.model` entries (excluding `system`)",
+        );
+        self.cfg.storage = StorageSettings::new(readahead, memlimit, force_load, skip_load);
+    }
+}
+
+// TCP settings
+#[allow(clippy::too_many_arguments)]
+impl Configset {
+    pub fn tcp_settings(
+        &mut self,
+        nnodelay: impl TryFromConfigSource<bool>,
+        nnodelay_key: StaticStr,
+        nkeepalive: impl TryFromConfigSource<u64>,
+        nkeepalive_key: StaticStr,
+        ninterval: impl TryFromConfigSource<u64>,
+        ninterval_key: StaticStr,
+        nsndbuf: impl TryFromConfigSource<usize>,
+        nsndbuf_key: StaticStr,
+        nrcvbuf: impl TryFromConfigSource<usize>,
+        nrcvbuf_key: StaticStr,
+    ) {
+        let mut tcp = TcpSettings::default();
+        self.try_mutate(nnodelay, &mut tcp.nodelay, nnodelay_key, "true/false");
+        self.try_mutate(
+            nkeepalive,
+            &mut tcp.keepalive,
+            nkeepalive_key,
+            "a positive integer (in seconds). 0 disables keepalive",
+        );
+        self.try_mutate(
+            ninterval,
+            &mut tcp.keepalive_interval,
+            ninterval_key,
+            "a positive integer (in seconds). 0 uses the OS default",
+        );
+        if tcp.keepalive_interval != 0 && tcp.keepalive == 0 {
+            self.wstack.push(format!(
+                "Specifying `{ninterval_key}` is pointless when keepalive is disabled"
+            ));
+        }
+        self.try_mutate(
+            nsndbuf,
+            &mut tcp.send_buffer,
+            nsndbuf_key,
+            "a positive integer (in bytes). 0 uses the OS default",
+        );
+        self.try_mutate(
+            nrcvbuf,
+            &mut tcp.recv_buffer,
+            nrcvbuf_key,
+            "a positive integer (in bytes). 0 uses the OS default",
+        );
+        self.cfg.tcp = tcp;
+    }
+}
+
+// rate limit settings
+impl Configset {
+    pub fn ratelimit_settings(
+        &mut self,
+        nconnection: impl TryFromConfigSource<u64>,
+        nconnection_key: StaticStr,
+        nuser: impl TryFromConfigSource<u64>,
+        nuser_key: StaticStr,
+    ) {
+        let mut ratelimit = RateLimit::default();
+        self.try_mutate(
+            nconnection,
+            &mut ratelimit.connection,
+            nconnection_key,
+            "a positive integer (queries per second). 0 disables the limit",
+        );
+        self.try_mutate(
+            nuser,
+            &mut ratelimit.user,
+            nuser_key,
+            "a positive integer (queries per second). 0 disables the limit",
+        );
+        self.cfg.ratelimit = ratelimit;
+    }
+}
+
 pub fn get_config() -> Result<ConfigType, ConfigError> {
     // initialize clap because that will let us check for CLI/file configs
     let cfg_layout = load_yaml!("../cli.yml");
     let matches = App::from_yaml(cfg_layout).get_matches();
     let restore_file = matches.value_of("restore").map(|v| v.to_string());
+    let task = self::get_task(&matches);
 
     // get config from file
     let cfg_from_file = if let Some(file) = matches.value_of("config") {
@@ -692,12 +1059,39 @@ pub fn get_config() -> Result<ConfigType, ConfigError> {
     if has_conflict {
         return Err(ConfigError::Conflict);
     }
-    if cfg_degree == 0 {
+    let cfg = if cfg_degree == 0 {
         // no configuration, use default
-        Ok(ConfigType::new_default(restore_file))
+        ConfigType::new_default(restore_file)
     } else {
         cfg_from_file
             .unwrap_or_else(|| cfg_from_env.and_then(cfg_from_cli))
-            .into_result(restore_file)
+            .into_result(restore_file)?
+    };
+    Ok(cfg.with_task(task))
+}
+
+/// Returns the task (if any) that should be run instead of starting the server
+fn get_task(matches: &ArgMatches) -> Option<Task> {
+    if let Some(backup) = matches.value_of("backup-info") {
+        return Some(Task::BackupInfo(backup.to_owned()));
+    }
+    if matches.is_present("selftest") {
+        return Some(Task::Selftest);
+    }
+    match matches.subcommand() {
+        ("import", Some(import)) => Some(Task::Import(ImportTask {
+            file: import.value_of("file").unwrap().to_owned(),
+            model: import.value_of("model").unwrap().to_owned(),
+            format: import.value_of("format").map(|v| v.to_owned()),
+            header: import.is_present("header"),
+            dry_run: import.is_present("dry-run"),
+        })),
+        ("export", Some(export)) => Some(Task::Export(ExportTask {
+            model: export.value_of("model").unwrap().to_owned(),
+            format: export.value_of("format").map(|v| v.to_owned()),
+            out: export.value_of("out").map(|v| v.to_owned()),
+        })),
+        ("lock-status", Some(_)) => Some(Task::LockStatus),
+        _ => None,
     }
 }
@@ -205,12 +205,40 @@ fn snapshot_okay() {
         "SKY_SNAPSHOT_ATMOST",
         Some("false"),
         "SKY_SNAPSHOT_FAILSAFE",
+        None,
+        "SKY_SNAPSHOT_ARCHIVE",
+    );
+    assert!(cfgset.is_mutated());
+    assert!(cfgset.is_okay());
+    assert_eq!(
+        cfgset.cfg.snapshot,
+        SnapshotConfig::Enabled(SnapshotPref::new(3600, 0, false, None))
+    );
+}
+
+#[test]
+fn snapshot_archive() {
+    let mut cfgset = Configset::new_env();
+    cfgset.snapshot_settings(
+        Some("3600"),
+        "SKY_SNAPSHOT_EVERY",
+        Some("4"),
+        "SKY_SNAPSHOT_ATMOST",
+        None,
+        "SKY_SNAPSHOT_FAILSAFE",
+        Some("/var/archive"),
+        "SKY_SNAPSHOT_ARCHIVE",
     );
     assert!(cfgset.is_mutated());
     assert!(cfgset.is_okay());
     assert_eq!(
         cfgset.cfg.snapshot,
-        SnapshotConfig::Enabled(SnapshotPref::new(3600, 0, false))
+        SnapshotConfig::Enabled(SnapshotPref::new(
+            3600,
+            4,
+            true,
+            Some("/var/archive".to_owned())
+        ))
     );
 }
 
@@ -224,6 +252,8 @@ fn snapshot_fail() {
         "SKY_SNAPSHOT_ATMOST",
         Some("falsee"),
         "SKY_SNAPSHOT_FAILSAFE",
+        None,
+        "SKY_SNAPSHOT_ARCHIVE",
     );
     assert!(cfgset.is_mutated());
     assert!(!cfgset.is_okay());
@@ -233,7 +263,7 @@ fn snapshot_fail() {
     );
     assert_eq!(
         cfgset.cfg.snapshot,
-        SnapshotConfig::Enabled(SnapshotPref::new(3600, 0, true))
+        SnapshotConfig::Enabled(SnapshotPref::new(3600, 0, true, None))
     );
 }
 
@@ -247,6 +277,8 @@ fn snapshot_fail_with_missing_required_values() {
         "SKY_SNAPSHOT_ATMOST",
         None,
         "SKY_SNAPSHOT_FAILSAFE",
+        None,
+        "SKY_SNAPSHOT_ARCHIVE",
     );
     assert!(cfgset.is_mutated());
     assert!(!cfgset.is_okay());
@@ -272,6 +304,45 @@ fn tls_settings_okay() {
         "SKY_TLS_ONLY",
         None,
         "SKY_TLS_PASSIN",
+        None,
+        "SKY_TLS_CLIENT_CA",
+        None,
+        "SKY_TLS_REQUIRE_CLIENT_CERT",
+    );
+    assert!(cfg.is_mutated());
+    assert!(cfg.is_okay());
+    assert_eq!(cfg.cfg.ports, {
+        let mut pf = PortConfig::default();
+        pf.upgrade_to_tls(SslOpts::new(
+            "key.pem".to_owned(),
+            "cert.pem".to_owned(),
+            2005,
+            None,
+            None,
+            false,
+        ));
+        pf
+    });
+}
+
+#[test]
+fn tls_settings_client_ca() {
+    let mut cfg = Configset::new_env();
+    cfg.tls_settings(
+        Some("key.pem"),
+        "SKY_TLS_KEY",
+        Some("cert.pem"),
+        "SKY_TLS_CERT",
+        Some("2005"),
+        "SKY_TLS_PORT",
+        Some("false"),
+        "SKY_TLS_ONLY",
+        None,
+        "SKY_TLS_PASSIN",
+        Some("ca.pem"),
+        "SKY_TLS_CLIENT_CA",
+        None,
+        "SKY_TLS_REQUIRE_CLIENT_CERT",
     );
     assert!(cfg.is_mutated());
     assert!(cfg.is_okay());
@@ -282,6 +353,43 @@ fn tls_settings_okay() {
             "cert.pem".to_owned(),
             2005,
             None,
+            Some("ca.pem".to_owned()),
+            false,
+        ));
+        pf
+    });
+}
+
+#[test]
+fn tls_settings_require_client_cert() {
+    let mut cfg = Configset::new_env();
+    cfg.tls_settings(
+        Some("key.pem"),
+        "SKY_TLS_KEY",
+        Some("cert.pem"),
+        "SKY_TLS_CERT",
+        Some("2005"),
+        "SKY_TLS_PORT",
+        Some("false"),
+        "SKY_TLS_ONLY",
+        None,
+        "SKY_TLS_PASSIN",
+        Some("ca.pem"),
+        "SKY_TLS_CLIENT_CA",
+        Some("true"),
+        "SKY_TLS_REQUIRE_CLIENT_CERT",
+    );
+    assert!(cfg.is_mutated());
+    assert!(cfg.is_okay());
+    assert_eq!(cfg.cfg.ports, {
+        let mut pf = PortConfig::default();
+        pf.upgrade_to_tls(SslOpts::new(
+            "key.pem".to_owned(),
+            "cert.pem".to_owned(),
+            2005,
+            None,
+            Some("ca.pem".to_owned()),
+            true,
         ));
         pf
     });
@@ -301,6 +409,10 @@ fn tls_settings_fail() {
         "SKY_TLS_ONLY",
         None,
         "SKY_TLS_PASSIN",
+        None,
+        "SKY_TLS_CLIENT_CA",
+        None,
+        "SKY_TLS_REQUIRE_CLIENT_CERT",
     );
     assert!(cfg.is_mutated());
     assert!(!cfg.is_okay());
@@ -311,6 +423,8 @@ fn tls_settings_fail() {
             "cert.pem".to_owned(),
             2004,
             None,
+            None,
+            false,
         ));
         pf
     });
@@ -330,6 +444,10 @@ fn tls_settings_fail_with_missing_required_values() {
         "SKY_TLS_ONLY",
         None,
         "SKY_TLS_PASSIN",
+        None,
+        "SKY_TLS_CLIENT_CA",
+        None,
+        "SKY_TLS_REQUIRE_CLIENT_CERT",
     );
     assert!(cfg.is_mutated());
     assert!(!cfg.is_okay());
@@ -347,7 +465,8 @@ mod cfg_file_tests {
     use crate::config::AuthkeyWrapper;
     use crate::config::{
         cfgfile, AuthSettings, BGSave, Configset, ConfigurationSet, Modeset, PortConfig,
-        ProtocolVersion, SnapshotConfig, SnapshotPref, SslOpts, DEFAULT_IPV4, DEFAULT_PORT,
+        ProtocolVersion, QueryLimits, RateLimit, SnapshotConfig, SnapshotPref, SslOpts,
+        StorageSettings, TcpSettings, DEFAULT_IPV4, DEFAULT_PORT,
     };
     use crate::dbnet::MAXIMUM_CONNECTION_LIMIT;
     use std::net::{IpAddr, Ipv6Addr};
@@ -366,7 +485,7 @@ mod cfg_file_tests {
         assert!(cfg_from_file.is_okay());
         // expected
         let mut expected = ConfigurationSet::default();
-        expected.snapshot = SnapshotConfig::Enabled(SnapshotPref::new(3600, 4, true));
+        expected.snapshot = SnapshotConfig::Enabled(SnapshotPref::new(3600, 4, true, None));
         expected.ports = PortConfig::new_secure_only(
             crate::config::DEFAULT_IPV4,
             SslOpts::new(
@@ -374,6 +493,8 @@ mod cfg_file_tests {
                 "/path/to/chain.pem".to_owned(),
                 2004,
                 Some("/path/to/cert/passphrase.txt".to_owned()),
+                None,
+                false,
             ),
         );
         expected.auth.origin_key =
@@ -401,9 +522,24 @@ mod cfg_file_tests {
                 snapshot: SnapshotConfig::default(),
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                max_pipeline: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                storage: StorageSettings::default(),
+                health_port: 0,
+                ephemeral: false,
+                admin_socket: None,
+                readonly: false,
+                admin_port: 0,
+                admin_local: false,
+                tcp: TcpSettings::default(),
+                ratelimit: RateLimit::default(),
+                limits: QueryLimits::default(),
+                metrics_endpoint: None,
+                conformance: false,
+                init_dir: None,
+                drain_timeout: 0,
             }
         );
     }
@@ -423,9 +559,24 @@ mod cfg_file_tests {
                     DEFAULT_PORT
                 ),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                max_pipeline: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                storage: StorageSettings::default(),
+                health_port: 0,
+                ephemeral: false,
+                admin_socket: None,
+                readonly: false,
+                admin_port: 0,
+                admin_local: false,
+                tcp: TcpSettings::default(),
+                ratelimit: RateLimit::default(),
+                limits: QueryLimits::default(),
+                metrics_endpoint: None,
+                conformance: false,
+                init_dir: None,
+                drain_timeout: 0,
             }
         );
     }
@@ -439,20 +590,37 @@ mod cfg_file_tests {
             ConfigurationSet::new(
                 false,
                 BGSave::default(),
-                SnapshotConfig::Enabled(SnapshotPref::new(3600, 4, true)),
+                SnapshotConfig::Enabled(SnapshotPref::new(3600, 4, true, None)),
                 PortConfig::new_secure_only(
                     DEFAULT_IPV4,
                     SslOpts::new(
                         "/path/to/keyfile.pem".into(),
                         "/path/to/chain.pem".into(),
                         2004,
-                        Some("/path/to/cert/passphrase.txt".to_owned())
+                        Some("/path/to/cert/passphrase.txt".to_owned()),
+                        None,
+                        false
                     )
                 ),
                 MAXIMUM_CONNECTION_LIMIT,
+                0,
                 Modeset::Dev,
                 AuthSettings::new(AuthkeyWrapper::try_new(crate::TEST_AUTH_ORIGIN_KEY).unwrap()),
-                ProtocolVersion::default()
+                ProtocolVersion::default(),
+                StorageSettings::default(),
+                0,
+                false,
+                None,
+                false,
+                0,
+                false,
+                TcpSettings::default(),
+                RateLimit::default(),
+                QueryLimits::default(),
+                None,
+                false,
+                None,
+                0
             )
         );
     }
@@ -476,9 +644,24 @@ mod cfg_file_tests {
                 snapshot: SnapshotConfig::default(),
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                max_pipeline: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                storage: StorageSettings::default(),
+                health_port: 0,
+                ephemeral: false,
+                admin_socket: None,
+                readonly: false,
+                admin_port: 0,
+                admin_local: false,
+                tcp: TcpSettings::default(),
+                ratelimit: RateLimit::default(),
+                limits: QueryLimits::default(),
+                metrics_endpoint: None,
+                conformance: false,
+                init_dir: None,
+                drain_timeout: 0,
             }
         );
     }
@@ -499,9 +682,24 @@ mod cfg_file_tests {
                 snapshot: SnapshotConfig::default(),
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                max_pipeline: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                storage: StorageSettings::default(),
+                health_port: 0,
+                ephemeral: false,
+                admin_socket: None,
+                readonly: false,
+                admin_port: 0,
+                admin_local: false,
+                tcp: TcpSettings::default(),
+                ratelimit: RateLimit::default(),
+                limits: QueryLimits::default(),
+                metrics_endpoint: None,
+                conformance: false,
+                init_dir: None,
+                drain_timeout: 0,
             }
         )
     }
@@ -522,9 +720,24 @@ mod cfg_file_tests {
                 snapshot: SnapshotConfig::default(),
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                max_pipeline: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                storage: StorageSettings::default(),
+                health_port: 0,
+                ephemeral: false,
+                admin_socket: None,
+                readonly: false,
+                admin_port: 0,
+                admin_local: false,
+                tcp: TcpSettings::default(),
+                ratelimit: RateLimit::default(),
+                limits: QueryLimits::default(),
+                metrics_endpoint: None,
+                conformance: false,
+                init_dir: None,
+                drain_timeout: 0,
             }
         )
     }
@@ -536,22 +749,50 @@ mod cfg_file_tests {
         assert_eq!(
             cfg.cfg,
             ConfigurationSet {
-                snapshot: SnapshotConfig::Enabled(SnapshotPref::new(3600, 4, true)),
+                snapshot: SnapshotConfig::Enabled(SnapshotPref::new(3600, 4, true, None)),
                 bgsave: BGSave::default(),
                 noart: false,
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                max_pipeline: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                storage: StorageSettings::default(),
+                health_port: 0,
+                ephemeral: false,
+                admin_socket: None,
+                readonly: false,
+                admin_port: 0,
+                admin_local: false,
+                tcp: TcpSettings::default(),
+                ratelimit: RateLimit::default(),
+                limits: QueryLimits::default(),
+                metrics_endpoint: None,
+                conformance: false,
+                init_dir: None,
+                drain_timeout: 0,
             }
         );
     }
+
+    #[test]
+    fn test_config_file_conformance_is_ephemeral() {
+        let file = "[server]\nhost = \"127.0.0.1\"\nport = 2003\nephemeral = false\nconformance_mode = true\n";
+        let cfg = cfgset_from_toml_str(file.to_owned()).unwrap();
+        assert!(cfg.is_okay());
+        assert!(cfg.cfg.conformance);
+        // the data directory must never be touched in conformance mode
+        assert!(cfg.cfg.ephemeral);
+    }
 }
 
 mod cli_arg_tests {
-    use crate::config::{cfgcli, PortConfig};
+    use crate::config::{
+        cfgcli, PortConfig, QueryLimits, RateLimit, SkipLoadSet, StorageSettings, TcpSettings,
+    };
     use clap::{load_yaml, App};
+    use std::net::SocketAddr;
     #[test]
     fn cli_args_okay() {
         let cfg_layout = load_yaml!("../cli.yml");
@@ -587,6 +828,220 @@ mod cli_arg_tests {
             "Bad value for `--port`. Expected a 16-bit positive integer"
         );
     }
+    #[test]
+    fn cli_args_readonly() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--readonly"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert!(ret.cfg.readonly);
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_tcp() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = [
+            "skyd",
+            "--tcp-nodelay",
+            "--tcp-keepalive",
+            "60",
+            "--tcp-keepalive-interval",
+            "10",
+            "--tcp-sndbuf",
+            "262144",
+        ];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(ret.cfg.tcp, TcpSettings::new(true, 60, 10, 262144, 0));
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_ratelimit() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = [
+            "skyd",
+            "--ratelimit-connection",
+            "500",
+            "--ratelimit-user",
+            "2000",
+        ];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(ret.cfg.ratelimit, RateLimit::new(500, 2000));
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_admin_port() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--admin-port", "2005", "--admin-local"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(ret.cfg.admin_port, 2005);
+        assert!(ret.cfg.admin_local);
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_init_dir() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--init-dir", "/etc/skyd/seeds"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(ret.cfg.init_dir.as_deref(), Some("/etc/skyd/seeds"));
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_drain_timeout() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--drain-timeout", "30"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(ret.cfg.drain_timeout, 30);
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    #[cfg(unix)]
+    fn cli_args_admin_socket() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--admin-socket", "/run/skyd/admin.sock"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(
+            ret.cfg.admin_socket.as_deref(),
+            Some("/run/skyd/admin.sock")
+        );
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_max_pipeline() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--maxpipeline", "1000"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(ret.cfg.max_pipeline, 1000);
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_query_limits() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--maxelements", "1024", "--maxquerysize", "1048576"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(ret.cfg.limits, QueryLimits::new(1024, 0, 1048576));
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_conformance_mode() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--conformance-mode"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert!(ret.cfg.conformance);
+        assert!(ret.cfg.ephemeral);
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_metrics_endpoint() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--metrics-endpoint", "127.0.0.1:2006"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(
+            ret.cfg.metrics_endpoint,
+            Some(SocketAddr::from(([127, 0, 0, 1], 2006)))
+        );
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+        // not an address
+        let cli_args = ["skyd", "--metrics-endpoint", "2006"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert!(!ret.is_okay());
+    }
+    #[test]
+    fn cli_args_ephemeral() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--ephemeral"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert!(ret.cfg.ephemeral);
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_health_port() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--health-port", "2010"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(ret.cfg.health_port, 2010);
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_memlimit() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--memlimit", "1024", "--force-load"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(
+            ret.cfg.storage,
+            StorageSettings::new(2, 1024, true, SkipLoadSet::new_empty())
+        );
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_readahead() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = ["skyd", "--readahead", "8"];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        assert_eq!(
+            ret.cfg.storage,
+            StorageSettings::new(8, 0, false, SkipLoadSet::new_empty())
+        );
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_skip_load() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        let cli_args = [
+            "skyd",
+            "--skip-load",
+            "twitter",
+            "--skip-load",
+            "default.big",
+        ];
+        let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+        let ret = cfgcli::parse_cli_args(matches);
+        let skip_load = &ret.cfg.storage.skip_load;
+        assert!(skip_load.skips_model("twitter", "users"));
+        assert!(skip_load.skips_model("default", "big"));
+        assert!(!skip_load.skips_model("default", "default"));
+        assert!(ret.is_mutated());
+        assert!(ret.is_okay());
+    }
+    #[test]
+    fn cli_args_skip_load_fail() {
+        let cfg_layout = load_yaml!("../cli.yml");
+        for bad in ["system", "default.", "1space", "a.b.c"] {
+            let cli_args = ["skyd", "--skip-load", bad];
+            let matches = App::from_yaml(cfg_layout).get_matches_from(cli_args);
+            let ret = cfgcli::parse_cli_args(matches);
+            assert!(!ret.is_okay(), "accepted {bad}");
+        }
+    }
 }
 
 mod try_from_config_source_impls {
@@ -28,6 +28,7 @@ use {
     crate::{
         actions::{translate_ddl_error, ActionResult},
         blueql::Entity,
+        config::StorageSettings,
         corestore::{
             memstore::{DdlError, Keyspace, Memstore, ObjectID, DEFAULT},
             table::{DescribeTable, Table},
@@ -106,15 +107,28 @@ pub struct Corestore {
     store: Arc<Memstore>,
     /// the snapshot engine
     sengine: Arc<SnapshotEngine>,
+    /// the fence token attached to this connection's writes (see `FENCE`)
+    fence: Option<u64>,
+    /// the temporary models created by this connection, as `(space, model, table)`. These are
+    /// never added to the keyspace (so they're never flushed) and they are dropped along with
+    /// the connection
+    temp_tables: Vec<(ObjectID, ObjectID, Arc<Table>)>,
 }
 
 impl Corestore {
     /// This is the only function you'll ever need to either create a new database instance
     /// or restore from an earlier instance
-    pub fn init_with_snapcfg(sengine: Arc<SnapshotEngine>) -> StorageEngineResult<Self> {
-        let store = storage::unflush::read_full()?;
+    pub fn init_with_snapcfg(
+        sengine: Arc<SnapshotEngine>,
+        settings: &StorageSettings,
+    ) -> StorageEngineResult<Self> {
+        let store = storage::unflush::read_full(settings)?;
         Ok(Self::default_with_store(store, sengine))
     }
+    /// Create a new, empty database instance that is never restored from or written to disk
+    pub fn init_ephemeral(sengine: Arc<SnapshotEngine>) -> Self {
+        Self::default_with_store(Memstore::new_default(), sengine)
+    }
     pub fn clone_store(&self) -> Arc<Memstore> {
         self.store.clone()
     }
@@ -125,6 +139,8 @@ impl Corestore {
             estate: ConnectionEntityState::default(cks, ctable),
             store: Arc::new(store),
             sengine,
+            fence: None,
+            temp_tables: Vec::new(),
         }
     }
     pub fn get_engine(&self) -> &SnapshotEngine {
@@ -151,7 +167,10 @@ impl Corestore {
             // Switch to the provided table in the given keyspace
             Entity::Full(ks, tbl) => {
                 match self.store.get_keyspace_atomic_ref(unsafe { ks.as_slice() }) {
-                    Some(kspace) => match kspace.get_table_atomic_ref(unsafe { tbl.as_slice() }) {
+                    Some(kspace) => match unsafe {
+                        self.get_temp_table(ks.as_slice(), tbl.as_slice())
+                            .or_else(|| kspace.get_table_atomic_ref(tbl.as_slice()))
+                    } {
                         Some(tblref) => unsafe {
                             self.estate.set_table(
                                 kspace,
@@ -197,7 +216,10 @@ impl Corestore {
                     .store
                     .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
                 {
-                    Some(ks) => match ks.get_table_atomic_ref(unsafe { table.as_slice() }) {
+                    Some(ks) => match unsafe {
+                        self.get_temp_table(ksid.as_slice(), table.as_slice())
+                            .or_else(|| ks.get_table_atomic_ref(table.as_slice()))
+                    } {
                         Some(tbl) => Ok(tbl),
                         None => Err(DdlError::ObjectNotFound),
                     },
@@ -205,7 +227,10 @@ impl Corestore {
                 }
             }
             Entity::Current(tbl) => match &self.estate.ks {
-                Some((_, ks)) => match ks.get_table_atomic_ref(unsafe { tbl.as_slice() }) {
+                Some((ksid, ks)) => match unsafe {
+                    self.get_temp_table(ksid, tbl.as_slice())
+                        .or_else(|| ks.get_table_atomic_ref(tbl.as_slice()))
+                } {
                     Some(tbl) => Ok(tbl),
                     None => Err(DdlError::ObjectNotFound),
                 },
@@ -213,6 +238,46 @@ impl Corestore {
             },
         }
     }
+    /// Returns a temporary model created by this connection
+    fn get_temp_table(&self, ksid: &[u8], tblid: &[u8]) -> Option<Arc<Table>> {
+        self.temp_tables
+            .iter()
+            .find(|(ks, tbl, _)| ks.as_slice() == ksid && tbl.as_slice() == tblid)
+            .map(|(_, _, table)| table.clone())
+    }
+    /// Returns the given table (or the current table) along with the IDs of its keyspace and
+    /// the table itself
+    pub fn resolve_table(
+        &self,
+        table: &Option<Entity>,
+    ) -> KeyspaceResult<(ObjectID, ObjectID, Arc<Table>)> {
+        match table {
+            Some(entity @ Entity::Full(ksid, tblid)) => unsafe {
+                Ok((
+                    ObjectID::from_slice(ksid.as_slice()),
+                    ObjectID::from_slice(tblid.as_slice()),
+                    self.get_table(entity)?,
+                ))
+            },
+            Some(entity @ Entity::Current(tblid)) => {
+                let tbl = self.get_table(entity)?;
+                match &self.estate.ks {
+                    Some((ksid, _)) => Ok((
+                        ksid.clone(),
+                        unsafe { ObjectID::from_slice(tblid.as_slice()) },
+                        tbl,
+                    )),
+                    None => Err(DdlError::DefaultNotFound),
+                }
+            }
+            None => match (&self.estate.ks, &self.estate.table) {
+                (Some((ksid, _)), Some((tblid, tbl))) => {
+                    Ok((ksid.clone(), tblid.clone(), tbl.clone()))
+                }
+                _ => Err(DdlError::DefaultNotFound),
+            },
+        }
+    }
     pub fn get_ctable(&self) -> Option<Arc<Table>> {
         self.estate.table.as_ref().map(|(_, tbl)| tbl.clone())
     }
@@ -294,8 +359,62 @@ impl Corestore {
         ret
     }
 
+    /// Create a temporary table that is only visible to this connection. Since it never makes it
+    /// into the keyspace, it is never flushed, and it is dropped along with this connection
+    pub fn create_temp_table(&mut self, entity: &Entity, modelcode: u8) -> KeyspaceResult<()> {
+        let (ksid, tblid) = match entity {
+            Entity::Current(tblid) => match &self.estate.ks {
+                Some((ksid, _)) => (ksid.clone(), tblid),
+                None => return Err(DdlError::DefaultNotFound),
+            },
+            Entity::Full(ksid, tblid) => {
+                if self.get_keyspace(unsafe { ksid.as_slice() }).is_none() {
+                    return Err(DdlError::ObjectNotFound);
+                }
+                (unsafe { ObjectID::from_slice(ksid.as_slice()) }, tblid)
+            }
+        };
+        // a temporary model can't shadow a model that already exists
+        if self.get_table(entity).is_ok() {
+            return Err(DdlError::AlreadyExists);
+        }
+        match Table::from_model_code(modelcode, true) {
+            Some(tbl) => {
+                let tblid = unsafe { ObjectID::from_slice(tblid.as_slice()) };
+                self.temp_tables.push((ksid, tblid, Arc::new(tbl)));
+                Ok(())
+            }
+            None => Err(DdlError::WrongModel),
+        }
+    }
+
+    /// Drop a temporary table, returning `None` if there is no such temporary table. Just like
+    /// other tables, it can't be dropped if it is in use or (unless forced) if it isn't empty
+    fn drop_temp_table(&mut self, entity: &Entity, force: bool) -> Option<KeyspaceResult<()>> {
+        let (ksid, tblid) = match entity {
+            Entity::Current(tblid) => (self.estate.ks.as_ref()?.0.as_slice(), tblid),
+            Entity::Full(ksid, tblid) => (unsafe { ksid.as_slice() }, tblid),
+        };
+        let tblid = unsafe { tblid.as_slice() };
+        let pos = self
+            .temp_tables
+            .iter()
+            .position(|(ks, tbl, _)| ks.as_slice() == ksid && tbl.as_slice() == tblid)?;
+        let table = &self.temp_tables[pos].2;
+        // 1 because this should just be us
+        if Arc::strong_count(table) == 1 && (table.is_empty() || force) {
+            self.temp_tables.swap_remove(pos);
+            Some(Ok(()))
+        } else {
+            Some(Err(DdlError::StillInUse))
+        }
+    }
+
     /// Drop a table
-    pub fn drop_table(&self, entity: &Entity, force: bool) -> KeyspaceResult<()> {
+    pub fn drop_table(&mut self, entity: &Entity, force: bool) -> KeyspaceResult<()> {
+        if let Some(ret) = self.drop_temp_table(entity, force) {
+            return ret;
+        }
         match entity {
             Entity::Current(tblid) => match &self.estate.ks {
                 Some((_, ks)) => ks.drop_table(unsafe { tblid.as_slice() }, force),
@@ -346,6 +465,13 @@ impl Corestore {
     pub fn strong_count(&self) -> usize {
         Arc::strong_count(&self.store)
     }
+    /// Attach a fence token to this connection's writes (or detach it)
+    pub fn set_fence(&mut self, fence: Option<u64>) {
+        self.fence = fence;
+    }
+    pub const fn get_fence(&self) -> Option<u64> {
+        self.fence
+    }
     pub fn get_ids(&self) -> (Option<&ObjectID>, Option<&ObjectID>) {
         self.estate.get_id_pack()
     }
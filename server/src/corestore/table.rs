@@ -35,6 +35,7 @@ use crate::{
     protocol::interface::ProtocolSpec,
     util,
 };
+use core::mem;
 
 pub trait DescribeTable {
     type Table;
@@ -45,6 +46,7 @@ pub trait DescribeTable {
                 // so we do have a table
                 match Self::try_get(table) {
                     Some(tbl) => Ok(tbl),
+                    None if table.is_unavailable() => util::err(P::RSTRING_MODEL_UNAVAILABLE),
                     None => util::err(P::RSTRING_WRONG_MODEL),
                 }
             }
@@ -106,6 +108,9 @@ impl SystemTable {
 pub enum DataModel {
     KV(KVEStandard),
     KVExtListmap(KVEListmap),
+    /// The data for this table wasn't loaded on startup (see `--skip-load`). We only hold on to
+    /// the model code so that the table (and its data on disk) is preserved
+    Unavailable(u8),
 }
 
 // same 8 byte ptrs; any chance of optimizations?
@@ -144,14 +149,49 @@ impl Table {
             Err(DdlError::WrongModel)
         }
     }
+    /// Returns an estimate of the memory used by the rows (the keys, the values and their
+    /// handles). This ignores the overhead of the allocator and of the map itself
+    pub fn memory_usage(&self) -> usize {
+        match &self.model_store {
+            DataModel::KV(kv) => kv
+                .get_inner_ref()
+                .iter()
+                .map(|row| {
+                    mem::size_of::<(SharedSlice, SharedSlice)>()
+                        + row.key().len()
+                        + row.value().len()
+                })
+                .sum(),
+            DataModel::KVExtListmap(kv) => kv
+                .get_inner_ref()
+                .iter()
+                .map(|row| {
+                    let list = row.value().read();
+                    mem::size_of::<(SharedSlice, LockedVec)>()
+                        + row.key().len()
+                        + list.capacity() * mem::size_of::<SharedSlice>()
+                        + list.iter().map(|element| element.len()).sum::<usize>()
+                })
+                .sum(),
+            DataModel::Unavailable(_) => 0,
+        }
+    }
     pub fn count(&self) -> usize {
         match &self.model_store {
             DataModel::KV(kv) => kv.len(),
             DataModel::KVExtListmap(kv) => kv.len(),
+            DataModel::Unavailable(_) => 0,
         }
     }
+    /// Returns true if this table's data wasn't loaded
+    pub const fn is_unavailable(&self) -> bool {
+        matches!(self.model_store, DataModel::Unavailable(_))
+    }
     /// Returns this table's _description_
     pub fn describe_self(&self) -> &'static str {
+        if self.is_unavailable() {
+            return "Unavailable { skipped:true }";
+        }
         match self.get_model_code() {
             // pure KV
             0 if self.is_volatile() => "Keymap { data:(binstr,binstr), volatile:true }",
@@ -174,14 +214,31 @@ impl Table {
             _ => unsafe { impossible!() },
         }
     }
+    /// Returns the model declaration, i.e the type list used in the `create model` statement
+    pub fn model_declaration(&self) -> &'static str {
+        match self.get_model_code() {
+            0 => "(binary, binary)",
+            1 => "(binary, string)",
+            2 => "(string, string)",
+            3 => "(string, binary)",
+            4 => "(binary, list<binary>)",
+            5 => "(binary, list<string>)",
+            6 => "(string, list<binary>)",
+            7 => "(string, list<string>)",
+            _ => unsafe { impossible!() },
+        }
+    }
     pub fn truncate_table(&self) {
         match self.model_store {
             DataModel::KV(ref kv) => kv.truncate_table(),
             DataModel::KVExtListmap(ref kv) => kv.truncate_table(),
+            DataModel::Unavailable(_) => {}
         }
     }
+    /// Returns true if the table is empty. Unavailable tables are never considered to be empty
+    /// since we don't know what's on disk
     pub fn is_empty(&self) -> bool {
-        self.count() == 0
+        !self.is_unavailable() && self.count() == 0
     }
     /// Returns the storage type as an 8-bit uint
     pub const fn storage_type(&self) -> u8 {
@@ -214,6 +271,13 @@ impl Table {
             model_store: DataModel::KVExtListmap(KVEListmap::new(k_enc, payload_enc, data)),
         }
     }
+    /// Create a placeholder for a table whose data isn't loaded
+    pub const fn new_unavailable(model_code: u8, volatile: bool) -> Self {
+        Self {
+            volatile,
+            model_store: DataModel::Unavailable(model_code),
+        }
+    }
     pub fn from_model_code(code: u8, volatile: bool) -> Option<Self> {
         macro_rules! pkve {
             ($kenc:expr, $venc:expr) => {
@@ -277,6 +341,7 @@ impl Table {
                 let (kenc, venc) = kvlistmap.get_encoding_tuple();
                 ((kenc as u8) << 1) + (venc as u8) + 4
             }
+            DataModel::Unavailable(model_code) => model_code,
         }
     }
     /// Returns the inner data model
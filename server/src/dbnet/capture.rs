@@ -0,0 +1,253 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Workload capture
+//!
+//! When a capture is active, (a sample of) all the queries run on the server are recorded
+//! along with their timings into a capture file in [`DIR_CAPTURES`]. Every record looks like:
+//! ```text
+//! #<start time in microseconds since the UNIX epoch> <latency in microseconds>\n
+//! <the query, encoded as a Skyhash 2 simple or pipelined query>\n
+//! ```
+//! so that the captured workload can be analyzed or replayed. Unless a raw capture is requested,
+//! all arguments are replaced by a hash of their value (which keeps the key distribution intact
+//! without leaking any data). The arguments to `AUTH` are always hashed
+
+use {
+    crate::{
+        protocol::{Query, UnsafeSlice},
+        storage::v1::interface::DIR_CAPTURES,
+        IoResult,
+    },
+    core::sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    parking_lot::Mutex,
+    std::{
+        collections::hash_map::DefaultHasher,
+        fs::{self, File, OpenOptions},
+        hash::Hasher,
+        io::{BufWriter, ErrorKind, Write},
+        path::Path,
+        time::{Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Whether a capture is active. This lets us avoid the lock when we're not capturing
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// The number of queries seen since the capture was started (used for sampling)
+static SEEN: AtomicU64 = AtomicU64::new(0);
+/// The active capture
+static CAPTURE: Mutex<Option<Capture>> = parking_lot::const_mutex(None);
+
+const ACTION_AUTH: &[u8] = b"auth";
+
+/// Result of starting a capture
+#[derive(Debug)]
+pub enum StartResult {
+    Started,
+    /// A capture is already active
+    AlreadyActive,
+    /// A capture with this name already exists
+    AlreadyExists,
+    /// Some other I/O error
+    IoError(std::io::Error),
+}
+
+struct Capture {
+    writer: BufWriter<File>,
+    /// capture one in every `every` queries
+    every: u64,
+    /// hash arguments instead of recording them
+    hashed: bool,
+    /// the number of queries captured so far
+    captured: u64,
+}
+
+/// Check if a capture name is legal. We only accept names made of alphanumeric characters,
+/// `_` and `-` so that a capture can never be written outside [`DIR_CAPTURES`]
+pub fn is_legal_name(name: &[u8]) -> bool {
+    !name.is_empty()
+        && name
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || *b == b'_' || *b == b'-')
+}
+
+/// Start capturing one in every `every` queries into a new capture file with the given name
+pub fn start(name: &str, every: u64, hashed: bool) -> StartResult {
+    let mut capture = CAPTURE.lock();
+    if capture.is_some() {
+        return StartResult::AlreadyActive;
+    }
+    if let Err(e) = fs::create_dir_all(DIR_CAPTURES) {
+        return StartResult::IoError(e);
+    }
+    let path = Path::new(DIR_CAPTURES).join(name);
+    let file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => return StartResult::AlreadyExists,
+        Err(e) => return StartResult::IoError(e),
+    };
+    *capture = Some(Capture {
+        writer: BufWriter::new(file),
+        every,
+        hashed,
+        captured: 0,
+    });
+    SEEN.store(0, Ordering::Relaxed);
+    ACTIVE.store(true, Ordering::Release);
+    StartResult::Started
+}
+
+/// Stop the active capture, returning the number of queries that were captured (or `None` if
+/// there was no active capture)
+pub fn stop() -> Option<IoResult<u64>> {
+    let mut capture = CAPTURE.lock();
+    ACTIVE.store(false, Ordering::Release);
+    capture
+        .take()
+        .map(|mut capture| capture.writer.flush().map(|_| capture.captured))
+}
+
+/// A query that has been sampled for capture
+pub struct Sampled {
+    started_at: u128,
+    timer: Instant,
+    encoded: Vec<u8>,
+}
+
+/// If a capture is active, and this query is picked by the sampler, encode the query so that
+/// it can be recorded once it completes (with [`Sampled::finish`])
+pub fn sample(query: &Query) -> Option<Sampled> {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return None;
+    }
+    let (every, hashed) = {
+        let capture = CAPTURE.lock();
+        let capture = capture.as_ref()?;
+        (capture.every, capture.hashed)
+    };
+    if SEEN.fetch_add(1, Ordering::Relaxed) % every != 0 {
+        return None;
+    }
+    let mut encoded = Vec::new();
+    match query {
+        Query::Simple(q) => {
+            encoded.push(b'*');
+            encode_stage(&mut encoded, q.as_slice(), hashed);
+        }
+        Query::Pipelined(p) => {
+            encoded.push(b'$');
+            encoded.extend(p.len().to_string().as_bytes());
+            encoded.push(b'\n');
+            for stage in p.as_slice() {
+                encode_stage(&mut encoded, stage, hashed);
+            }
+        }
+    }
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros())
+        .unwrap_or(0);
+    Some(Sampled {
+        started_at,
+        timer: Instant::now(),
+        encoded,
+    })
+}
+
+impl Sampled {
+    /// Record the query in the capture file (if the capture is still active)
+    pub fn finish(self) {
+        let latency = self.timer.elapsed().as_micros();
+        let mut capture = CAPTURE.lock();
+        let ret = match capture.as_mut() {
+            Some(capture) => {
+                let ret = writeln!(capture.writer, "#{} {}", self.started_at, latency)
+                    .and_then(|_| capture.writer.write_all(&self.encoded))
+                    .and_then(|_| capture.writer.write_all(b"\n"));
+                capture.captured += 1;
+                ret
+            }
+            None => return,
+        };
+        if let Err(e) = ret {
+            log::error!("Failed to write to capture file: {e}. Stopping capture");
+            ACTIVE.store(false, Ordering::Release);
+            *capture = None;
+        }
+    }
+}
+
+/// Encode a single stage. The action is always recorded as-is
+fn encode_stage(buf: &mut Vec<u8>, stage: &[UnsafeSlice], hashed: bool) {
+    buf.extend(stage.len().to_string().as_bytes());
+    buf.push(b'\n');
+    let mut hash_args = hashed;
+    for (i, element) in stage.iter().enumerate() {
+        let element = unsafe {
+            // UNSAFE(@ohsayan): The query is valid as long as the buffer is
+            element.as_slice()
+        };
+        if i == 0 {
+            hash_args |= element.eq_ignore_ascii_case(ACTION_AUTH);
+            encode_element(buf, element);
+        } else if hash_args {
+            let mut hasher = DefaultHasher::new();
+            hasher.write(element);
+            encode_element(buf, format!("{:016x}", hasher.finish()).as_bytes());
+        } else {
+            encode_element(buf, element);
+        }
+    }
+}
+
+fn encode_element(buf: &mut Vec<u8>, element: &[u8]) {
+    buf.extend(element.len().to_string().as_bytes());
+    buf.push(b'\n');
+    buf.extend(element);
+}
+
+#[test]
+fn test_encode_stage() {
+    let to_stage = |elements: &[&[u8]]| -> Vec<UnsafeSlice> {
+        elements
+            .iter()
+            .map(|e| UnsafeSlice::new(e.as_ptr(), e.len()))
+            .collect()
+    };
+    let mut buf = Vec::new();
+    encode_stage(&mut buf, &to_stage(&[b"SET", b"x", b"100"]), false);
+    assert_eq!(buf, b"3\n3\nSET1\nx3\n100");
+    // auth arguments are always hashed
+    let mut buf = Vec::new();
+    encode_stage(&mut buf, &to_stage(&[b"AUTH", b"login", b"root"]), false);
+    assert!(buf.starts_with(b"3\n4\nAUTH16\n"));
+    assert!(!buf.windows(4).any(|w| w == b"root"));
+    // the same value always hashes the same way
+    let mut buf = Vec::new();
+    encode_stage(&mut buf, &to_stage(&[b"GET", b"x", b"x"]), true);
+    assert_eq!(buf.len(), "3\n3\nGET".len() + 2 * "16\n".len() + 2 * 16);
+    assert_eq!(buf[10..26], buf[29..45]);
+}
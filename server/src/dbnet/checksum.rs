@@ -0,0 +1,184 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Response checksums
+//!
+//! A client can ask for a checksum of every response (see the `CHECKSUM` action) to detect
+//! corruption that slipped past TCP's checksums (faulty NICs, proxies and the like). The
+//! checksum is a CRC-32 (IEEE) computed on the bytes as they are written to the socket, so
+//! there's no extra pass over the response. Once the response is complete, we append a trailer
+//! that looks like `#<8 hex digits>\n`, which isn't part of the checksum itself
+
+use {
+    crate::IoResult,
+    core::{
+        pin::Pin,
+        task::{Context, Poll},
+    },
+    tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf},
+};
+
+/// The CRC-32 lookup table for the reflected IEEE polynomial
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Update the (unfinalized) CRC with the given bytes
+fn crc_update(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc = CRC_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+/// Returns the CRC-32 of the given bytes
+#[cfg(test)]
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    !crc_update(!0, data)
+}
+
+/// A stream that keeps a running checksum of everything written to it (when enabled)
+pub struct Checksummed<T> {
+    inner: T,
+    enabled: bool,
+    crc: u32,
+}
+
+impl<T> Checksummed<T> {
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner,
+            enabled: false,
+            crc: !0,
+        }
+    }
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    /// Enable or disable the checksums, starting a new checksum either way
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.crc = !0;
+    }
+    /// Returns the checksum of everything written since the last reset, and resets it
+    fn take(&mut self) -> u32 {
+        let crc = !self.crc;
+        self.crc = !0;
+        crc
+    }
+}
+
+impl<T: AsyncWrite + Unpin> Checksummed<T> {
+    /// Write the trailer with the checksum of the response, bypassing the checksum itself. The
+    /// response must have been flushed already
+    pub async fn write_trailer(&mut self) -> IoResult<()> {
+        let trailer = format!("#{:08x}\n", self.take());
+        self.inner.write_all(trailer.as_bytes()).await?;
+        self.inner.flush().await
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Checksummed<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Checksummed<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        let ret = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = ret {
+            if self.enabled {
+                self.crc = crc_update(self.crc, &buf[..written]);
+            }
+        }
+        ret
+    }
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{crc32, crc_update, Checksummed},
+        tokio::io::AsyncWriteExt,
+    };
+
+    #[test]
+    fn crc32_check_value() {
+        // the standard check value for CRC-32/ISO-HDLC
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        // splitting the input doesn't change the checksum
+        assert_eq!(!crc_update(crc_update(!0, b"1234"), b"56789"), 0xCBF43926);
+    }
+
+    #[tokio::test]
+    async fn trailer_per_response() {
+        let mut stream = Checksummed::new(Vec::new());
+        // nothing is checksummed until enabled
+        stream.write_all(b"+0\n").await.unwrap();
+        stream.set_enabled(true);
+        stream.write_all(b"1234").await.unwrap();
+        stream.write_all(b"56789").await.unwrap();
+        stream.write_trailer().await.unwrap();
+        // the checksum is reset after every trailer
+        stream.write_all(b"123456789").await.unwrap();
+        stream.write_trailer().await.unwrap();
+        assert_eq!(
+            stream.inner,
+            b"+0\n123456789#cbf43926\n123456789#cbf43926\n".to_vec()
+        );
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Conformance mode
+//!
+//! In conformance mode (`--conformance-mode`) the server always runs ephemerally, starts with a
+//! fixed set of fixtures and lets clients script faults, so that client library authors can run
+//! a standard conformance suite against the real server. The fixtures are:
+//! - `default.default`: `fixture:ascii`, `fixture:unicode`, `fixture:binary` (all 256 byte
+//! values) and `fixture:empty`
+//! - `conformance.strings` (`keymap(str,str)`): `fixture:ascii`, `fixture:unicode` and
+//! `fixture:empty`
+//! - `conformance.lists` (`keymap(str,list<str>)`): `fixture:letters` and `fixture:empty`
+//!
+//! Faults are scripted per connection with the `FAULT` action, which only exists in this mode:
+//! - `FAULT DELAY <ms>`: delay the response to the next query by `ms` milliseconds
+//! - `FAULT ERROR <code>`: respond to the next query with the response code `code` (`0`-`9`)
+//! - `FAULT CLEAR`: clear all scripted faults
+//!
+//! Capabilities can be permuted by starting the server with different options (like
+//! `--protover`, `--auth-origin-key` or `--maxpipeline`)
+
+use {
+    crate::{
+        corestore::{
+            memstore::{Memstore, ObjectID, DEFAULT},
+            table::{DataModel, Table},
+            Corestore, SharedSlice,
+        },
+        kvengine::LockedVec,
+        protocol::{interface::ProtocolSpec, Query},
+    },
+    core::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration,
+    },
+};
+
+/// Whether conformance mode is enabled
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+const ACTION_FAULT: &[u8] = b"fault";
+const FAULT_DELAY: &[u8] = b"delay";
+const FAULT_ERROR: &[u8] = b"error";
+const FAULT_CLEAR: &[u8] = b"clear";
+const FIXTURE_ASCII: &str = "fixture:ascii";
+const FIXTURE_UNICODE: &str = "fixture:unicode";
+const FIXTURE_BINARY: &str = "fixture:binary";
+const FIXTURE_EMPTY: &str = "fixture:empty";
+const FIXTURE_LETTERS: &str = "fixture:letters";
+
+/// Enable conformance mode and load the fixtures into the given (empty) store
+pub fn enable(db: &Corestore) {
+    ENABLED.store(true, Ordering::Release);
+    load_fixtures(db.get_store());
+}
+
+/// Check if conformance mode is enabled
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+fn load_fixtures(store: &Memstore) {
+    let default = store
+        .get_keyspace_atomic_ref(&DEFAULT)
+        .and_then(|ks| ks.get_table_atomic_ref(&DEFAULT));
+    if let Some(DataModel::KV(kve)) = default.as_deref().map(Table::get_model_ref) {
+        let binary: Vec<u8> = (0..=u8::MAX).collect();
+        let rows = kve.get_inner_ref();
+        rows.upsert(FIXTURE_ASCII.into(), "hello, world".into());
+        rows.upsert(FIXTURE_UNICODE.into(), "héllo, wörld ✓ 🚀".into());
+        rows.upsert(FIXTURE_BINARY.into(), binary.into());
+        rows.upsert(FIXTURE_EMPTY.into(), SharedSlice::from(""));
+    }
+    let ksid = unsafe {
+        // UNSAFE(@ohsayan): the name is well within the limits of an object ID
+        ObjectID::from_slice("conformance")
+    };
+    store.create_keyspace(ksid.clone());
+    if let Some(ks) = store.get_keyspace_atomic_ref(&ksid) {
+        let strings = Table::from_model_code(2, false).unwrap();
+        if let DataModel::KV(kve) = strings.get_model_ref() {
+            let rows = kve.get_inner_ref();
+            rows.upsert(FIXTURE_ASCII.into(), "hello, world".into());
+            rows.upsert(FIXTURE_UNICODE.into(), "héllo, wörld ✓ 🚀".into());
+            rows.upsert(FIXTURE_EMPTY.into(), SharedSlice::from(""));
+        }
+        let lists = Table::from_model_code(7, false).unwrap();
+        if let DataModel::KVExtListmap(kve) = lists.get_model_ref() {
+            let rows = kve.get_inner_ref();
+            rows.upsert(
+                FIXTURE_LETTERS.into(),
+                LockedVec::new(vec!["a".into(), "b".into(), "c".into()]),
+            );
+            rows.upsert(FIXTURE_EMPTY.into(), LockedVec::new(vec![]));
+        }
+        unsafe {
+            // UNSAFE(@ohsayan): the names are well within the limits of an object ID
+            ks.create_table(ObjectID::from_slice("strings"), strings);
+            ks.create_table(ObjectID::from_slice("lists"), lists);
+        }
+    }
+}
+
+/// The faults scripted on a connection. Every fault only applies to the next query
+#[derive(Default)]
+pub struct Faults {
+    delay: Option<Duration>,
+    error: Option<u8>,
+}
+
+/// The outcome of a `FAULT` action
+pub enum Scripted {
+    Okay,
+    /// The arguments were invalid
+    Invalid,
+}
+
+impl Faults {
+    /// If the query is a `FAULT` action, apply it and return the outcome. Otherwise return
+    /// `None` (the query should be run)
+    pub fn script(&mut self, query: &Query) -> Option<Scripted> {
+        let stage = match query {
+            Query::Simple(q) => q.as_slice(),
+            Query::Pipelined(_) => return None,
+        };
+        let arg = |idx: usize| {
+            stage.get(idx).map(|arg| unsafe {
+                // UNSAFE(@ohsayan): The query is valid as long as the buffer is
+                arg.as_slice()
+            })
+        };
+        if !arg(0)?.eq_ignore_ascii_case(ACTION_FAULT) {
+            return None;
+        }
+        let number = |arg: Option<&[u8]>| {
+            arg.and_then(|arg| core::str::from_utf8(arg).ok())
+                .and_then(|arg| arg.parse::<u64>().ok())
+        };
+        let outcome = match (arg(1), stage.len()) {
+            (Some(kind), 3) if kind.eq_ignore_ascii_case(FAULT_DELAY) => match number(arg(2)) {
+                Some(ms) => {
+                    self.delay = Some(Duration::from_millis(ms));
+                    Scripted::Okay
+                }
+                None => Scripted::Invalid,
+            },
+            (Some(kind), 3) if kind.eq_ignore_ascii_case(FAULT_ERROR) => match number(arg(2)) {
+                Some(code) if code <= 9 => {
+                    self.error = Some(code as u8);
+                    Scripted::Okay
+                }
+                _ => Scripted::Invalid,
+            },
+            (Some(kind), 2) if kind.eq_ignore_ascii_case(FAULT_CLEAR) => {
+                *self = Self::default();
+                Scripted::Okay
+            }
+            _ => Scripted::Invalid,
+        };
+        Some(outcome)
+    }
+    /// Take the delay scripted for this query, if any
+    pub fn take_delay(&mut self) -> Option<Duration> {
+        self.delay.take()
+    }
+    /// Take the response code scripted for this query, if any
+    pub fn take_error<P: ProtocolSpec>(&mut self) -> Option<&'static [u8]> {
+        self.error.take().map(|code| match code {
+            0 => P::RCODE_OKAY,
+            1 => P::RCODE_NIL,
+            2 => P::RCODE_OVERWRITE_ERR,
+            3 => P::RCODE_ACTION_ERR,
+            4 => P::RCODE_PACKET_ERR,
+            5 => P::RCODE_SERVER_ERR,
+            6 => P::RCODE_OTHER_ERR_EMPTY,
+            7 => P::RCODE_WRONGTYPE_ERR,
+            8 => P::RCODE_UNKNOWN_DATA_TYPE,
+            _ => P::RCODE_ENCODING_ERROR,
+        })
+    }
+}
@@ -25,13 +25,14 @@
 */
 
 use {
-    super::{BufferedSocketStream, QueryResult},
+    super::{checksum::Checksummed, BufferedSocketStream, QueryResult},
     crate::{
         corestore::buffers::Integer64,
         protocol::{interface::ProtocolSpec, ParseError},
+        services::metrics,
         IoResult,
     },
-    bytes::BytesMut,
+    bytes::{Buf, BytesMut},
     std::{
         io::{Error as IoError, ErrorKind},
         marker::PhantomData,
@@ -48,16 +49,19 @@ const BUF_READ_CAP: usize = 8192;
 /// 1. A stream (TCP, TLS(TCP), UDS, ...)
 /// 2. A protocol (one that implements [`ProtocolSpec`])
 pub struct Connection<T, P> {
-    pub(super) stream: BufWriter<T>,
+    pub(super) stream: BufWriter<Checksummed<T>>,
     pub(super) buffer: BytesMut,
+    /// whether the client wants response checksums (applied from the next response onwards)
+    checksums: bool,
     _marker: PhantomData<P>,
 }
 
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     pub fn new(stream: T) -> Self {
         Connection {
-            stream: BufWriter::with_capacity(BUF_WRITE_CAP, stream),
+            stream: BufWriter::with_capacity(BUF_WRITE_CAP, Checksummed::new(stream)),
             buffer: BytesMut::with_capacity(BUF_READ_CAP),
+            checksums: false,
             _marker: PhantomData,
         }
     }
@@ -68,6 +72,13 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     /// Attempt to read a query
     pub(super) async fn read_query(&mut self) -> IoResult<QueryResult> {
         loop {
+            // we might already have a complete packet buffered (if the client sent multiple
+            // packets at once, or if we just skipped a malformed packet)
+            if !self.buffer.is_empty() {
+                if let Some(ret) = self.try_decode().await? {
+                    return Ok(ret);
+                }
+            }
             match self.stream.read_buf(&mut self.buffer).await {
                 Ok(0) => {
                     if self.buffer.is_empty() {
@@ -81,20 +92,72 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
                 Ok(_) => {}
                 Err(e) => return Err(e),
             }
-            // see if we have buffered enough data to run anything
-            match P::decode_packet(self.buffer.as_ref()) {
-                Ok(query_with_advance) => return Ok(QueryResult::Q(query_with_advance)),
-                Err(ParseError::NotEnough) => {}
-                Err(e) => {
-                    self.write_error(P::SKYHASH_PARSE_ERROR_LUT[e as usize - 1])
-                        .await?;
-                    return Ok(QueryResult::NextLoop);
+        }
+    }
+    /// See if we have buffered enough data to run anything. Returns `None` if we need more data
+    async fn try_decode(&mut self) -> IoResult<Option<QueryResult>> {
+        match P::decode_packet(self.buffer.as_ref()) {
+            Ok(query_with_advance) => Ok(Some(QueryResult::Q(query_with_advance))),
+            Err(ParseError::NotEnough) => Ok(None),
+            Err(ParseError::QueryTooLarge) => {
+                metrics::record_too_large();
+                // the rest of the query is probably still on its way, so we can't find the
+                // next packet reliably
+                self.write_error(P::FULLRESP_RSTRING_QUERY_TOO_LARGE)
+                    .await?;
+                self.finish_response().await?;
+                Ok(Some(QueryResult::Disconnected))
+            }
+            Err(e) => {
+                self.write_error(P::SKYHASH_PARSE_ERROR_LUT[e as usize - 1])
+                    .await?;
+                // the error is a response of its own, so it gets its own checksum
+                self.finish_response().await?;
+                // don't let the malformed packet poison the ones that follow it
+                match next_packet_offset::<P>(self.buffer.as_ref()) {
+                    Some(skip) => {
+                        self.buffer.advance(skip);
+                        Ok(Some(QueryResult::NextLoop))
+                    }
+                    // this is mostly garbage, so there's no point in looking any further
+                    None => Ok(Some(QueryResult::Disconnected)),
                 }
             }
         }
     }
 }
 
+/// The maximum number of offsets that we attempt to decode a packet at, while skipping a
+/// malformed packet
+const MAX_RESYNC_ATTEMPTS: usize = 32;
+
+/// Returns the offset of the first complete packet after the malformed packet at the start
+/// of `buf`. If there isn't one, the length of the buffer is returned (i.e everything is to
+/// be discarded). We only skip to complete packets since a partial packet found while scanning
+/// is most likely just a part of the malformed packet.
+///
+/// A packet is only looked for right after a LF and we give up (returning `None`) after
+/// [`MAX_RESYNC_ATTEMPTS`] attempts, so that a large buffer of garbage can't keep us decoding
+/// over and over again
+fn next_packet_offset<P: ProtocolSpec>(buf: &[u8]) -> Option<usize> {
+    let mut attempts = 0;
+    for i in 1..buf.len() {
+        let is_header =
+            buf[i] == P::SIMPLE_QUERY_HEADER[0] || buf[i] == P::PIPELINED_QUERY_FIRST_BYTE;
+        if buf[i - 1] != P::LF || !is_header {
+            continue;
+        }
+        if attempts == MAX_RESYNC_ATTEMPTS {
+            return None;
+        }
+        attempts += 1;
+        if P::decode_packet(&buf[i..]).is_ok() {
+            return Some(i);
+        }
+    }
+    Some(buf.len())
+}
+
 // protocol write (metaframe)
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     /// Write a simple query header to the stream
@@ -113,6 +176,27 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     }
 }
 
+// response checksums
+impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
+    /// Enable or disable response checksums. This only applies from the next response onwards,
+    /// so that a response never has a partial checksum
+    pub fn set_checksums(&mut self, enabled: bool) {
+        self.checksums = enabled;
+    }
+    /// Finish the current response, by writing its checksum if checksums are enabled
+    pub(super) async fn finish_response(&mut self) -> IoResult<()> {
+        if self.stream.get_ref().is_enabled() {
+            // everything needs to go through the checksum before we can write it out
+            self.stream.flush().await?;
+            self.stream.get_mut().write_trailer().await?;
+        }
+        if self.stream.get_ref().is_enabled() != self.checksums {
+            self.stream.get_mut().set_enabled(self.checksums);
+        }
+        Ok(())
+    }
+}
+
 // protocol write (helpers)
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     /// Write an error to the stream (just used to differentiate between "normal" and "errored" writes)
@@ -246,3 +330,78 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{next_packet_offset, Connection, QueryResult, MAX_RESYNC_ATTEMPTS},
+        crate::{
+            dbnet::{checksum, BufferedSocketStream},
+            protocol::{interface::ProtocolSpec, Skyhash2},
+        },
+        tokio::io::{self, AsyncReadExt, AsyncWriteExt, DuplexStream},
+    };
+
+    impl BufferedSocketStream for DuplexStream {}
+
+    #[test]
+    fn test_next_packet_offset() {
+        // a malformed packet followed by a good one
+        let buf = b"*2\nxyz\n*2\n3\nGET1\nx";
+        assert!(Skyhash2::decode_packet(buf).is_err());
+        assert_eq!(next_packet_offset::<Skyhash2>(buf), Some(7));
+        // a malformed packet followed by a partial packet
+        let buf = b"*2\nxyz\n*2\n3\nGET1";
+        assert_eq!(next_packet_offset::<Skyhash2>(buf), Some(buf.len()));
+        // a malformed packet followed by a good pipeline
+        let buf = b"*a\n$1\n2\n3\nGET1\nx";
+        assert_eq!(next_packet_offset::<Skyhash2>(buf), Some(3));
+        // nothing but garbage
+        let buf = b"\x00\x01*$";
+        assert_eq!(next_packet_offset::<Skyhash2>(buf), Some(buf.len()));
+        // headers that aren't at the start of a line aren't even tried
+        let buf = b"*2\nxyz*2\n3\nGET1\nx";
+        assert_eq!(next_packet_offset::<Skyhash2>(buf), Some(buf.len()));
+        // too many packets to try
+        let buf = b"*2\nxyz\n".repeat(MAX_RESYNC_ATTEMPTS + 2);
+        assert_eq!(next_packet_offset::<Skyhash2>(&buf), None);
+    }
+
+    fn with_trailer(response: &[u8]) -> Vec<u8> {
+        let trailer = format!("#{:08x}\n", checksum::crc32(response));
+        [response, trailer.as_bytes()].concat()
+    }
+
+    #[tokio::test]
+    async fn checksum_after_malformed_packet() {
+        const MALFORMED: &[u8] = b"*2\nxyz\n";
+        const RESPONSE: &[u8] = b"!0\n";
+        let error = match Skyhash2::decode_packet(MALFORMED) {
+            Err(e) => Skyhash2::SKYHASH_PARSE_ERROR_LUT[e as usize - 1],
+            Ok(_) => panic!("expected a malformed packet"),
+        };
+        let (mut client, server) = io::duplex(1024);
+        let mut con = Connection::<_, Skyhash2>::new(server);
+        con.set_checksums(true);
+        con.finish_response().await.unwrap();
+        client
+            .write_all(&[MALFORMED, b"*2\n3\nGET1\nx"].concat())
+            .await
+            .unwrap();
+        assert!(matches!(
+            con.read_query().await.unwrap(),
+            QueryResult::NextLoop
+        ));
+        assert!(matches!(con.read_query().await.unwrap(), QueryResult::Q(_)));
+        con._write_raw(RESPONSE).await.unwrap();
+        con.finish_response().await.unwrap();
+        drop(con);
+        let mut received = Vec::new();
+        client.read_to_end(&mut received).await.unwrap();
+        // the error's bytes must not leak into the checksum of the next response
+        assert_eq!(
+            received,
+            [with_trailer(error), with_trailer(RESPONSE)].concat()
+        );
+    }
+}
@@ -0,0 +1,152 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Write fences
+//!
+//! External coordinators (such as failover managers) acquire fence tokens with
+//! `SYS FENCE ACQUIRE`. Tokens increase monotonically and (unless we're running ephemerally)
+//! survive restarts. A connection attaches a token to its writes with `FENCE <token>`, after
+//! which its writes are refused once a newer fence has been acquired or once its fence has been
+//! released. This keeps a writer that has been failed over from writing alongside its successor
+
+use {
+    crate::{registry, IoResult},
+    parking_lot::Mutex,
+    std::{
+        fs,
+        io::{Error as IoError, ErrorKind},
+    },
+};
+
+/// The file that holds the latest fence
+const FENCE_FILE: &str = "data/FENCE";
+/// The temporary file used while the fence file is being replaced
+const FENCE_FILE_TMP: &str = "data/FENCE_";
+
+/// The latest fence
+static FENCE: Mutex<Fence> = parking_lot::const_mutex(Fence::new());
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Fence {
+    /// the latest token (`0` if no fence has ever been acquired)
+    token: u64,
+    /// whether the latest fence has been released
+    released: bool,
+}
+
+impl Fence {
+    const fn new() -> Self {
+        Self {
+            token: 0,
+            released: true,
+        }
+    }
+    fn encode(&self) -> String {
+        format!("{} {}\n", self.token, self.released as u8)
+    }
+    fn decode(src: &str) -> Option<Self> {
+        let mut parts = src.split_whitespace();
+        let token = parts.next()?.parse().ok()?;
+        let released = match parts.next()? {
+            "0" => false,
+            "1" => true,
+            _ => return None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self { token, released })
+    }
+    /// Persist the fence. Nothing is persisted in ephemeral mode
+    fn persist(&self) -> IoResult<()> {
+        if registry::is_ephemeral() {
+            return Ok(());
+        }
+        fs::write(FENCE_FILE_TMP, self.encode())?;
+        fs::rename(FENCE_FILE_TMP, FENCE_FILE)
+    }
+}
+
+/// Load the latest fence from disk
+pub fn load() -> IoResult<()> {
+    let fence = match fs::read_to_string(FENCE_FILE) {
+        Ok(fence) => Fence::decode(&fence)
+            .ok_or_else(|| IoError::new(ErrorKind::InvalidData, "corrupted fence file"))?,
+        Err(e) if e.kind() == ErrorKind::NotFound => Fence::new(),
+        Err(e) => return Err(e),
+    };
+    *FENCE.lock() = fence;
+    Ok(())
+}
+
+/// Acquire a new fence, returning its token. The fence is persisted before the token is
+/// handed out
+pub fn acquire() -> IoResult<u64> {
+    let mut fence = FENCE.lock();
+    let next = Fence {
+        token: fence.token + 1,
+        released: false,
+    };
+    next.persist()?;
+    *fence = next;
+    Ok(next.token)
+}
+
+/// Release the fence with the given token. Returns false if that isn't the latest fence or if it
+/// has already been released
+pub fn release(token: u64) -> IoResult<bool> {
+    let mut fence = FENCE.lock();
+    if fence.token != token || fence.released {
+        return Ok(false);
+    }
+    let next = Fence {
+        token,
+        released: true,
+    };
+    next.persist()?;
+    *fence = next;
+    Ok(true)
+}
+
+/// Returns true if writes carrying the given token can be accepted, i.e if it is the latest
+/// fence and it hasn't been released
+pub fn is_current(token: u64) -> bool {
+    let fence = FENCE.lock();
+    fence.token == token && !fence.released
+}
+
+#[test]
+fn fence_encode_decode() {
+    let fence = Fence {
+        token: 42,
+        released: false,
+    };
+    assert_eq!(Fence::decode(&fence.encode()), Some(fence));
+    assert_eq!(Fence::decode("42"), None);
+    assert_eq!(Fence::decode("42 2"), None);
+    assert_eq!(Fence::decode("42 1 1"), None);
+    assert_eq!(Fence::decode("-1 0"), None);
+}
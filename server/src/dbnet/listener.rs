@@ -26,24 +26,29 @@
 
 use {
     super::{
+        ratelimit::RateLimiter,
         tcp::{Listener, ListenerV1},
         tls::{SslListener, SslListenerV1},
     },
     crate::{
         auth::AuthProvider,
-        config::{PortConfig, ProtocolVersion, SslOpts},
+        config::{PortConfig, ProtocolVersion, RateLimit, SslOpts, TcpSettings},
         corestore::Corestore,
         util::error::{Error, SkyResult},
         IoResult,
     },
-    core::future::Future,
+    core::{future::Future, time::Duration},
+    socket2::{SockRef, TcpKeepalive},
     std::{net::IpAddr, sync::Arc},
     tokio::{
-        net::TcpListener,
+        net::{TcpListener, TcpStream},
         sync::{broadcast, mpsc, Semaphore},
     },
 };
 
+/// The maximum number of concurrent connections on the admin endpoint
+const ADMIN_CONNECTION_LIMIT: usize = 16;
+
 /// The base TCP listener
 pub struct BaseListener {
     /// An atomic reference to the coretable
@@ -54,6 +59,14 @@ pub struct BaseListener {
     pub listener: TcpListener,
     /// The maximum number of connections
     pub climit: Arc<Semaphore>,
+    /// The maximum number of queries in a pipeline (`0` for no limit)
+    pub max_pipeline: usize,
+    /// Whether this is the admin endpoint (only accepting administrative actions)
+    pub admin_only: bool,
+    /// The socket options to set on accepted connections
+    pub tcp: TcpSettings,
+    /// The query rate limiter
+    pub ratelimit: RateLimiter,
     /// The shutdown broadcaster
     pub signal: broadcast::Sender<()>,
     // When all `Sender`s are dropped - the `Receiver` gets a `None` value
@@ -63,12 +76,16 @@ pub struct BaseListener {
 }
 
 impl BaseListener {
+    #[allow(clippy::too_many_arguments)]
     pub async fn init(
         db: &Corestore,
         auth: AuthProvider,
         host: IpAddr,
         port: u16,
         semaphore: Arc<Semaphore>,
+        max_pipeline: usize,
+        tcp: TcpSettings,
+        ratelimit: RateLimiter,
         signal: broadcast::Sender<()>,
     ) -> SkyResult<Self> {
         let (terminate_tx, terminate_rx) = mpsc::channel(1);
@@ -80,18 +97,70 @@ impl BaseListener {
             auth,
             listener,
             climit: semaphore,
+            max_pipeline,
+            admin_only: false,
+            tcp,
+            ratelimit,
             signal,
             terminate_tx,
             terminate_rx,
         })
     }
+    /// Set the configured socket options on an accepted connection
+    pub fn set_socket_options(&self, stream: &TcpStream) -> IoResult<()> {
+        let TcpSettings {
+            nodelay,
+            keepalive,
+            keepalive_interval,
+            send_buffer,
+            recv_buffer,
+        } = self.tcp;
+        if nodelay {
+            stream.set_nodelay(true)?;
+        }
+        let socket = SockRef::from(stream);
+        if keepalive != 0 {
+            let params = TcpKeepalive::new().with_time(Duration::from_secs(keepalive));
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "macos",
+                target_os = "windows"
+            ))]
+            let params = if keepalive_interval != 0 {
+                params.with_interval(Duration::from_secs(keepalive_interval))
+            } else {
+                params
+            };
+            socket.set_tcp_keepalive(&params)?;
+        }
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "macos",
+            target_os = "windows"
+        )))]
+        let _ = keepalive_interval;
+        if send_buffer != 0 {
+            socket.set_send_buffer_size(send_buffer)?;
+        }
+        if recv_buffer != 0 {
+            socket.set_recv_buffer_size(recv_buffer)?;
+        }
+        Ok(())
+    }
     pub async fn release_self(self) {
         let Self {
+            listener,
             mut terminate_rx,
             terminate_tx,
             signal,
             ..
         } = self;
+        // stop accepting connections while the existing ones drain
+        drop(listener);
         drop(signal);
         drop(terminate_tx);
         let _ = terminate_rx.recv().await;
@@ -153,6 +222,8 @@ impl MultiListener {
                     ssl.chain,
                     base,
                     ssl.passfile,
+                    ssl.clientca,
+                    ssl.require_client_cert,
                 )?;
                 MultiListener::SecureOnly(listener)
             }
@@ -162,6 +233,8 @@ impl MultiListener {
                     ssl.chain,
                     base,
                     ssl.passfile,
+                    ssl.clientca,
+                    ssl.require_client_cert,
                 )?;
                 MultiListener::SecureOnlyV1(listener)
             }
@@ -182,6 +255,8 @@ impl MultiListener {
                     ssl.chain,
                     ssl_base_listener,
                     ssl.passfile,
+                    ssl.clientca,
+                    ssl.require_client_cert,
                 )?;
                 let insecure_listener = Listener::new(tcp_base_listener);
                 MultiListener::Multi(insecure_listener, secure_listener)
@@ -192,6 +267,8 @@ impl MultiListener {
                     ssl.chain,
                     ssl_base_listener,
                     ssl.passfile,
+                    ssl.clientca,
+                    ssl.require_client_cert,
                 )?;
                 let insecure_listener = ListenerV1::new(tcp_base_listener);
                 MultiListener::MultiV1(insecure_listener, secure_listener)
@@ -228,27 +305,31 @@ impl MultiListener {
             | MultiListener::InsecureOnlyV1(ListenerV1 { base, .. })
             | MultiListener::SecureOnlyV1(SslListenerV1 { base, .. }) => base.release_self().await,
             MultiListener::Multi(insecure, secure) => {
-                insecure.base.release_self().await;
-                secure.base.release_self().await;
+                tokio::join!(insecure.base.release_self(), secure.base.release_self());
             }
             MultiListener::MultiV1(insecure, secure) => {
-                insecure.base.release_self().await;
-                secure.base.release_self().await;
+                tokio::join!(insecure.base.release_self(), secure.base.release_self());
             }
         }
     }
 }
 
 /// Initialize the database networking
+#[allow(clippy::too_many_arguments)]
 pub async fn connect(
     ports: PortConfig,
     protocol: ProtocolVersion,
     maxcon: usize,
+    max_pipeline: usize,
+    tcp: TcpSettings,
+    ratelimit: RateLimit,
     db: Corestore,
     auth: AuthProvider,
     signal: broadcast::Sender<()>,
 ) -> SkyResult<MultiListener> {
     let climit = Arc::new(Semaphore::new(maxcon));
+    // the per-user limits are shared across all the listeners
+    let ratelimit = RateLimiter::new(ratelimit);
     let base_listener_init = |host, port| {
         BaseListener::init(
             &db,
@@ -256,6 +337,9 @@ pub async fn connect(
             host,
             port,
             climit.clone(),
+            max_pipeline,
+            tcp,
+            ratelimit.clone(),
             signal.clone(),
         )
     };
@@ -278,3 +362,24 @@ pub async fn connect(
     log::info!("Server started on {description}");
     Ok(server)
 }
+
+/// Initialize the admin endpoint. It has its own connection limit so that operators can still
+/// connect when the application endpoint is saturated
+pub async fn connect_admin(
+    host: IpAddr,
+    port: u16,
+    protocol: ProtocolVersion,
+    tcp: TcpSettings,
+    db: Corestore,
+    auth: AuthProvider,
+    signal: broadcast::Sender<()>,
+) -> SkyResult<MultiListener> {
+    let climit = Arc::new(Semaphore::new(ADMIN_CONNECTION_LIMIT));
+    // operators might need to run large pipelines for maintenance and are never rate limited
+    let ratelimit = RateLimiter::new(RateLimit::default());
+    let mut base =
+        BaseListener::init(&db, auth, host, port, climit, 0, tcp, ratelimit, signal).await?;
+    base.admin_only = true;
+    log::info!("Admin endpoint listening on skyhash://{host}:{port}");
+    Ok(MultiListener::new_insecure_only(base, protocol))
+}
@@ -0,0 +1,216 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Local admin socket
+//!
+//! The admin socket is a Unix domain socket that only users on the same host (who can access
+//! the socket file) can connect to. Connections on it are always authenticated as `root`, so
+//! that an operator can still run administrative actions if the root token has been lost (for
+//! example, to regenerate it with `AUTH RESTORE root`). Every action run on this socket is
+//! logged
+
+use {
+    super::{BufferedSocketStream, Connection, ConnectionHandler, NetBackoff},
+    crate::{
+        auth::AuthProvider,
+        corestore::Corestore,
+        protocol::Skyhash2,
+        util::error::{Error, SkyResult},
+        IoResult,
+    },
+    std::{
+        fs::{self, DirBuilder, Permissions},
+        io::ErrorKind,
+        os::unix::{
+            fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+            net::UnixStream as StdUnixStream,
+        },
+        path::{Path, PathBuf},
+        process,
+        sync::Arc,
+    },
+    tokio::{
+        net::{UnixListener, UnixStream},
+        sync::{broadcast, mpsc, Semaphore},
+    },
+};
+
+/// The maximum number of concurrent connections on the admin socket
+const ADMIN_CONNECTION_LIMIT: usize = 16;
+
+impl BufferedSocketStream for UnixStream {}
+
+/// A listener for the local admin socket
+pub struct LocalListener {
+    db: Corestore,
+    auth: AuthProvider,
+    listener: UnixListener,
+    path: PathBuf,
+    climit: Arc<Semaphore>,
+    signal: broadcast::Sender<()>,
+    terminate_tx: mpsc::Sender<()>,
+    terminate_rx: mpsc::Receiver<()>,
+}
+
+/// Bind to a socket at `path` that is only accessible by the user running the server. The
+/// socket is created inside a private (`0700`) directory and only moved into place once its
+/// permissions have been restricted, so that it's never reachable with the default (umask)
+/// permissions
+fn bind_private(path: &Path) -> IoResult<UnixListener> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let private_dir = parent.join(format!(".skyd-admin-{}", process::id()));
+    // left behind by an earlier run that crashed (with the same PID)
+    match fs::remove_dir_all(&private_dir) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    DirBuilder::new().mode(0o700).create(&private_dir)?;
+    let private_path = private_dir.join("admin.sock");
+    let ret = UnixListener::bind(&private_path).and_then(|listener| {
+        fs::set_permissions(&private_path, Permissions::from_mode(0o600))?;
+        fs::rename(&private_path, path)?;
+        Ok(listener)
+    });
+    let _ = fs::remove_dir_all(&private_dir);
+    ret
+}
+
+/// Remove the socket left behind at `path` by an earlier run that didn't shut down cleanly.
+/// Anything else at `path` (a regular file, or the socket of a server that's still running) is
+/// left alone and returns an error
+fn remove_stale_socket(path: &Path) -> SkyResult<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(Error::ioerror_extra(e, "checking for a stale admin socket")),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(Error::OtherError(format!(
+            "{} already exists and isn't a socket",
+            path.display()
+        )));
+    }
+    if StdUnixStream::connect(path).is_ok() {
+        return Err(Error::OtherError(format!(
+            "another server is already listening on the admin socket at {}",
+            path.display()
+        )));
+    }
+    fs::remove_file(path).map_err(|e| Error::ioerror_extra(e, "removing stale admin socket"))?;
+    log::warn!("Removed stale admin socket at {}", path.display());
+    Ok(())
+}
+
+impl LocalListener {
+    /// Bind to the socket at the given path. The socket file is only accessible by the user
+    /// running the server
+    pub fn bind(
+        path: String,
+        db: &Corestore,
+        auth: AuthProvider,
+        signal: broadcast::Sender<()>,
+    ) -> SkyResult<Self> {
+        let path = PathBuf::from(path);
+        // a stale socket from an earlier run will make the bind fail
+        self::remove_stale_socket(&path)?;
+        let listener =
+            bind_private(&path).map_err(|e| Error::ioerror_extra(e, "binding to admin socket"))?;
+        log::info!("Admin socket listening on {}", path.display());
+        let (terminate_tx, terminate_rx) = mpsc::channel(1);
+        Ok(Self {
+            db: db.clone(),
+            auth,
+            listener,
+            path,
+            climit: Arc::new(Semaphore::new(ADMIN_CONNECTION_LIMIT)),
+            signal,
+            terminate_tx,
+            terminate_rx,
+        })
+    }
+    /// Accept an incoming connection
+    async fn accept(&mut self) -> IoResult<UnixStream> {
+        let backoff = NetBackoff::new();
+        loop {
+            match self.listener.accept().await {
+                Ok((stream, _)) => return Ok(stream),
+                Err(e) => {
+                    if backoff.should_disconnect() {
+                        return Err(e);
+                    }
+                }
+            }
+            backoff.spin().await;
+        }
+    }
+    /// Run the listener
+    pub async fn run(&mut self) -> IoResult<()> {
+        loop {
+            self.climit.acquire().await.unwrap().forget();
+            let stream = skip_loop_err!(self.accept().await);
+            log::warn!("[admin] Accepted connection on admin socket");
+            let mut chandle = ConnectionHandler::<UnixStream, Skyhash2>::new(
+                self.db.clone(),
+                Connection::new(stream),
+                self.auth.clone(),
+                self.climit.clone(),
+                // operators might need to run large pipelines for maintenance
+                0,
+                self.signal.subscribe(),
+                self.terminate_tx.clone(),
+            );
+            chandle.elevate();
+            tokio::spawn(async move {
+                if let Err(e) = chandle.run().await {
+                    log::error!("Error: {}", e);
+                }
+                log::warn!("[admin] Connection on admin socket closed");
+            });
+        }
+    }
+    /// Remove the socket file and then wait for all connections to terminate
+    pub async fn finish_with_termsig(self) {
+        let Self {
+            listener,
+            mut terminate_rx,
+            terminate_tx,
+            signal,
+            path,
+            ..
+        } = self;
+        // stop accepting connections while the existing ones drain
+        drop(listener);
+        if let Err(e) = fs::remove_file(&path) {
+            log::error!("Failed to remove admin socket: {e}");
+        }
+        drop(signal);
+        drop(terminate_tx);
+        let _ = terminate_rx.recv().await;
+    }
+}
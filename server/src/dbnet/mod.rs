@@ -25,17 +25,26 @@
 */
 
 use {
-    self::connection::Connection,
+    self::{
+        conformance::{Faults, Scripted},
+        connection::Connection,
+        ratelimit::ConnectionLimiter,
+    },
     crate::{
         actions::{ActionError, ActionResult},
         auth::AuthProvider,
         corestore::Corestore,
-        protocol::{interface::ProtocolSpec, Query},
+        protocol::{interface::ProtocolSpec, Query, UnsafeSlice},
+        services::{history, metrics},
         util::compiler,
         IoResult,
     },
     bytes::Buf,
-    std::{cell::Cell, sync::Arc, time::Duration},
+    std::{
+        cell::Cell,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
     tokio::{
         io::{AsyncReadExt, AsyncWriteExt},
         sync::{
@@ -51,13 +60,20 @@ pub type QueryWithAdvance = (Query, usize);
 pub const MAXIMUM_CONNECTION_LIMIT: usize = 50000;
 use crate::queryengine;
 
-pub use self::listener::connect;
+pub use self::listener::{connect, connect_admin};
 
+pub mod capture;
+mod checksum;
+pub mod conformance;
 mod connection;
+pub mod fence;
 #[macro_use]
 mod macros;
 mod listener;
+#[cfg(unix)]
+pub mod local;
 pub mod prelude;
+mod ratelimit;
 mod tcp;
 mod tls;
 
@@ -143,12 +159,22 @@ pub struct ConnectionHandler<C, P> {
     con: Connection<C, P>,
     /// the semaphore used to impose limits on number of connections
     climit: Arc<Semaphore>,
+    /// the maximum number of queries in a pipeline (`0` for no limit)
+    max_pipeline: usize,
     /// the authentication handle
     auth: AuthProviderHandle,
     /// check for termination signals
     termination_signal: broadcast::Receiver<()>,
     /// the sender that we drop when we're done with handling a connection (used for gracefule exit)
     _term_sig_tx: mpsc::Sender<()>,
+    /// if set, the connection bypasses authentication and all queries are audit logged
+    privileged: bool,
+    /// if set, only administrative actions can be run on this connection
+    admin_only: bool,
+    /// the rate limiter for this connection (if any limits are set)
+    ratelimit: Option<ConnectionLimiter>,
+    /// the faults scripted on this connection (only in conformance mode)
+    faults: Faults,
 }
 
 impl<C, P> ConnectionHandler<C, P>
@@ -162,25 +188,82 @@ where
         con: Connection<C, P>,
         auth_data: AuthProvider,
         climit: Arc<Semaphore>,
+        max_pipeline: usize,
         termination_signal: broadcast::Receiver<()>,
         _term_sig_tx: mpsc::Sender<()>,
     ) -> Self {
+        metrics::connection_opened();
         Self {
             db,
             con,
             climit,
+            max_pipeline,
             auth: AuthProviderHandle::new(auth_data),
             termination_signal,
             _term_sig_tx,
+            privileged: false,
+            admin_only: false,
+            ratelimit: None,
+            faults: Faults::default(),
+        }
+    }
+    /// Authenticate this connection as root and audit log every query run on it. This must
+    /// **only** be used for connections from trusted sources (like the local admin socket)
+    pub fn elevate(&mut self) {
+        self.auth.provider_mut().assume_root();
+        self.auth.set_auth();
+        self.privileged = true;
+    }
+    /// Authenticate this connection as the given user, if the user exists. This must **only** be
+    /// used for identities that were verified by other means (like a TLS client certificate)
+    pub fn authenticate_as(&mut self, user: &[u8]) -> bool {
+        let authenticated = self.auth.provider_mut().assume_user(user);
+        if authenticated {
+            self.auth.set_auth();
+        }
+        authenticated
+    }
+    /// Only allow administrative actions on this connection (used for the admin endpoint)
+    pub fn restrict_to_admin(&mut self) {
+        self.admin_only = true;
+    }
+    /// Limit the rate at which queries can be run on this connection
+    pub fn set_rate_limit(&mut self, limiter: Option<ConnectionLimiter>) {
+        self.ratelimit = limiter;
+    }
+    /// Log the actions in the given query (we never log the arguments since they might contain
+    /// secrets)
+    fn audit(query: &Query) {
+        let action = |stage: &[UnsafeSlice]| {
+            stage
+                .first()
+                .map(|action| {
+                    String::from_utf8_lossy(unsafe {
+                        // UNSAFE(@ohsayan): The query is valid as long as the buffer is
+                        action.as_slice()
+                    })
+                    .to_ascii_uppercase()
+                })
+                .unwrap_or_default()
+        };
+        match query {
+            Query::Simple(q) => log::warn!("[admin] {}", action(q.as_slice())),
+            Query::Pipelined(p) => {
+                for stage in p.as_slice() {
+                    log::warn!("[admin] {} (pipelined)", action(stage));
+                }
+            }
         }
     }
     pub async fn run(&mut self) -> IoResult<()> {
         loop {
             let packet = tokio::select! {
-                pkt = self.con.read_query() => pkt,
+                // don't pick up new queries once we've been asked to shut down
+                biased;
                 _ = self.termination_signal.recv() => {
                     return Ok(());
                 }
+                pkt = self.con.read_query() => pkt,
             };
             match packet {
                 Ok(QueryResult::Q((query, advance))) => {
@@ -194,11 +277,15 @@ where
                     let eptr_at_start = sptr_at_start + len_at_start;
                     {
                         // The actual execution (the assertions are just debug build sanity checks)
-                        match self.execute_query(query).await {
+                        let started = Instant::now();
+                        let ret = self.execute_query(query).await;
+                        history::record_query(started.elapsed(), advance, ret.is_err());
+                        match ret {
                             Ok(()) => {}
                             Err(ActionError::ActionError(e)) => self.con.write_error(e).await?,
                             Err(ActionError::IoError(e)) => return Err(e),
                         }
+                        self.con.finish_response().await?;
                     }
                     {
                         // do these assertions to ensure memory safety (this is just for sanity sake)
@@ -230,7 +317,68 @@ where
         }
     }
     async fn execute_query(&mut self, query: Query) -> ActionResult<()> {
-        let Self { db, con, auth, .. } = self;
+        if self.privileged {
+            Self::audit(&query);
+        }
+        let sampled = capture::sample(&query);
+        self.respond(query).await?;
+        // refused queries are captured too, so that a replay sees everything the server saw
+        if let Some(sampled) = sampled {
+            sampled.finish();
+        }
+        Ok(())
+    }
+    /// Run the query (or refuse it) and write the response
+    async fn respond(&mut self, query: Query) -> ActionResult<()> {
+        let Self {
+            db,
+            con,
+            auth,
+            max_pipeline,
+            admin_only,
+            ratelimit,
+            faults,
+            ..
+        } = self;
+        if conformance::is_enabled() {
+            if let Some(scripted) = faults.script(&query) {
+                con.write_simple_query_header().await?;
+                match scripted {
+                    Scripted::Okay => con._write_raw(P::RCODE_OKAY).await?,
+                    Scripted::Invalid => con.write_error(P::RCODE_ACTION_ERR).await?,
+                }
+                con.stream.flush().await?;
+                return Ok(());
+            }
+            if let Some(delay) = faults.take_delay() {
+                time::sleep(delay).await;
+            }
+            if let Some(rcode) = faults.take_error::<P>() {
+                con.write_simple_query_header().await?;
+                con._write_raw(rcode).await?;
+                con.stream.flush().await?;
+                return Ok(());
+            }
+        }
+        if *admin_only && !queryengine::is_admin_query(&query) {
+            con.write_simple_query_header().await?;
+            con.write_error(P::RSTRING_NOT_ADMIN_ACTION).await?;
+            con.stream.flush().await?;
+            return Ok(());
+        }
+        if let Some(limiter) = ratelimit {
+            let count = match &query {
+                Query::Simple(_) => 1,
+                Query::Pipelined(p) => p.len() as u64,
+            };
+            if !limiter.try_acquire(auth.provider().current_user(), count) {
+                metrics::record_rate_limited();
+                con.write_simple_query_header().await?;
+                con.write_error(P::RSTRING_RATE_LIMITED).await?;
+                con.stream.flush().await?;
+                return Ok(());
+            }
+        }
         match query {
             Query::Simple(q) => {
                 con.write_simple_query_header().await?;
@@ -241,7 +389,10 @@ where
                 }
             }
             Query::Pipelined(p) => {
-                if compiler::likely(auth.authenticated()) {
+                if *max_pipeline != 0 && p.len() > *max_pipeline {
+                    con.write_simple_query_header().await?;
+                    con.write_error(P::RSTRING_PIPELINE_TOO_LONG).await?;
+                } else if compiler::likely(auth.authenticated()) {
                     con.write_pipelined_query_header(p.len()).await?;
                     queryengine::execute_pipeline(db, con, auth, p).await?;
                 } else {
@@ -260,5 +411,6 @@ impl<C, T> Drop for ConnectionHandler<C, T> {
         // Make sure that the permit is returned to the semaphore
         // in the case that there is a panic inside
         self.climit.add_permits(1);
+        metrics::connection_closed();
     }
 }
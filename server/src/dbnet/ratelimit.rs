@@ -0,0 +1,140 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Rate limiting
+//!
+//! Queries are counted in one second windows, both per connection and per user (across all of
+//! the user's connections). Once a limit is hit, the queries are rejected with
+//! `rate-limited` until the window ends, so that a runaway client can't starve everyone else.
+//! Connections on the admin endpoint and the local admin socket are never limited
+
+use {
+    crate::config::RateLimit,
+    parking_lot::Mutex,
+    std::{
+        collections::HashMap,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+};
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// The number of queries run in the current window
+struct Window {
+    start: Instant,
+    used: u64,
+}
+
+impl Window {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            used: 0,
+        }
+    }
+    /// Try to take `count` queries from the window, returning false if that would exceed
+    /// `limit`
+    fn try_take(&mut self, limit: u64, count: u64, now: Instant) -> bool {
+        if now.duration_since(self.start) >= WINDOW {
+            self.start = now;
+            self.used = 0;
+        }
+        let okay = self.used.saturating_add(count) <= limit;
+        if okay {
+            self.used += count;
+        }
+        okay
+    }
+}
+
+/// The rate limiter shared by all the connections of a listener
+#[derive(Clone)]
+pub struct RateLimiter {
+    limits: RateLimit,
+    users: Arc<Mutex<HashMap<Box<[u8]>, Window>>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimit) -> Self {
+        Self {
+            limits,
+            users: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+    /// Returns a limiter for a new connection, if any limits are set
+    pub fn for_connection(&self) -> Option<ConnectionLimiter> {
+        if self.limits.is_enabled() {
+            Some(ConnectionLimiter {
+                shared: self.clone(),
+                window: Window::new(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// The rate limiter for a single connection
+pub struct ConnectionLimiter {
+    shared: RateLimiter,
+    window: Window,
+}
+
+impl ConnectionLimiter {
+    /// Try to run `count` queries as `user` (if logged in), returning false if either the
+    /// connection or the user limit would be exceeded
+    pub fn try_acquire(&mut self, user: Option<&[u8]>, count: u64) -> bool {
+        let RateLimit {
+            connection: connection_limit,
+            user: user_limit,
+        } = self.shared.limits;
+        let now = Instant::now();
+        if connection_limit != 0 && !self.window.try_take(connection_limit, count, now) {
+            return false;
+        }
+        match user {
+            Some(user) if user_limit != 0 => {
+                let mut users = self.shared.users.lock();
+                let okay = match users.get_mut(user) {
+                    Some(window) => window.try_take(user_limit, count, now),
+                    None => {
+                        let mut window = Window::new();
+                        let okay = window.try_take(user_limit, count, now);
+                        users.insert(user.into(), window);
+                        okay
+                    }
+                };
+                if !okay && connection_limit != 0 {
+                    // these queries aren't run, so don't count them against the connection
+                    self.window.used -= count;
+                }
+                okay
+            }
+            _ => true,
+        }
+    }
+}
@@ -60,7 +60,13 @@ impl<P: ProtocolSpec + 'static> RawListener<P> {
         loop {
             match self.base.listener.accept().await {
                 // We don't need the bindaddr
-                Ok((stream, _)) => return Ok(stream),
+                Ok((stream, _)) => {
+                    if let Err(e) = self.base.set_socket_options(&stream) {
+                        // the options are best-effort; the connection works fine without them
+                        log::warn!("Failed to set the socket options for a connection: {e}");
+                    }
+                    return Ok(stream);
+                }
                 Err(e) => {
                     if backoff.should_disconnect() {
                         // Too many retries, goodbye user
@@ -92,9 +98,15 @@ impl<P: ProtocolSpec + 'static> RawListener<P> {
                 Connection::new(stream),
                 self.base.auth.clone(),
                 self.base.climit.clone(),
+                self.base.max_pipeline,
                 self.base.signal.subscribe(),
                 self.base.terminate_tx.clone(),
             );
+            if self.base.admin_only {
+                chandle.restrict_to_admin();
+            } else {
+                chandle.set_rate_limit(self.base.ratelimit.for_connection());
+            }
             tokio::spawn(async move {
                 if let Err(e) = chandle.run().await {
                     log::error!("Error: {}", e);
@@ -30,13 +30,16 @@ use {
             listener::BaseListener, BufferedSocketStream, Connection, ConnectionHandler, NetBackoff,
         },
         protocol::{interface::ProtocolSpec, Skyhash1, Skyhash2},
+        services::metrics,
         util::error::{Error, SkyResult},
         IoResult,
     },
     openssl::{
+        nid::Nid,
         pkey::PKey,
         rsa::Rsa,
-        ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod},
+        ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod, SslVerifyMode},
+        x509::X509Name,
     },
     std::{fs, marker::PhantomData, pin::Pin},
     tokio::net::TcpStream,
@@ -60,6 +63,8 @@ impl<P: ProtocolSpec + 'static> SslListenerRaw<P> {
         chain_file: String,
         base: BaseListener,
         tls_passfile: Option<String>,
+        tls_clientca: Option<String>,
+        require_client_cert: bool,
     ) -> SkyResult<SslListenerRaw<P>> {
         let mut acceptor_builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
         // cert is the same for both
@@ -80,6 +85,19 @@ impl<P: ProtocolSpec + 'static> SslListenerRaw<P> {
             // no passphrase, needs interactive
             acceptor_builder.set_private_key_file(key_file, SslFiletype::PEM)?;
         }
+        if let Some(tls_clientca) = tls_clientca {
+            // the certificates that clients present need to be signed by one of these CAs
+            acceptor_builder.set_ca_file(&tls_clientca)?;
+            acceptor_builder.set_client_ca_list(X509Name::load_client_ca_file(&tls_clientca)?);
+            let verify_mode = if require_client_cert {
+                SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT
+            } else {
+                // clients without a certificate can still log in with `AUTH LOGIN`
+                SslVerifyMode::PEER
+            };
+            acceptor_builder.set_verify(verify_mode);
+            acceptor_builder.set_session_id_context(b"skyd")?;
+        }
         Ok(Self {
             acceptor: acceptor_builder.build(),
             base,
@@ -94,9 +112,16 @@ impl<P: ProtocolSpec + 'static> SslListenerRaw<P> {
                 // We get the encrypted stream which we need to decrypt
                 // by using the acceptor
                 Ok((stream, _)) => {
+                    if let Err(e) = self.base.set_socket_options(&stream) {
+                        // the options are best-effort; the connection works fine without them
+                        log::warn!("Failed to set the socket options for a connection: {e}");
+                    }
                     let ssl = Ssl::new(self.acceptor.context())?;
                     let mut stream = SslStream::new(ssl, stream)?;
-                    Pin::new(&mut stream).accept().await?;
+                    if let Err(e) = Pin::new(&mut stream).accept().await {
+                        metrics::record_tls_handshake_failure();
+                        return Err(e.into());
+                    }
                     return Ok(stream);
                 }
                 Err(e) => {
@@ -110,6 +135,32 @@ impl<P: ProtocolSpec + 'static> SslListenerRaw<P> {
             backoff.spin().await;
         }
     }
+    /// Returns the names in the (verified) certificate presented by the client, if any. That is
+    /// the common name, followed by the DNS names and email addresses in the subject alternative
+    /// names
+    fn peer_names(stream: &SslStream<TcpStream>) -> Vec<String> {
+        let cert = match stream.ssl().peer_certificate() {
+            Some(cert) => cert,
+            None => return Vec::new(),
+        };
+        let mut names: Vec<String> = cert
+            .subject_name()
+            .entries_by_nid(Nid::COMMONNAME)
+            .next()
+            .and_then(|cn| cn.data().as_utf8().ok())
+            .map(|cn| cn.to_string())
+            .into_iter()
+            .collect();
+        if let Some(alt_names) = cert.subject_alt_names() {
+            names.extend(
+                alt_names
+                    .iter()
+                    .filter_map(|name| name.dnsname().or_else(|| name.email()))
+                    .map(str::to_owned),
+            );
+        }
+        names
+    }
     pub async fn run(&mut self) -> IoResult<()> {
         loop {
             // Take the permit first, but we won't use it right now
@@ -124,14 +175,24 @@ impl<P: ProtocolSpec + 'static> SslListenerRaw<P> {
              in a crash
             */
             let stream = skip_loop_err!(self.accept().await);
+            let peer_names = Self::peer_names(&stream);
             let mut sslhandle = ConnectionHandler::<SslStream<TcpStream>, P>::new(
                 self.base.db.clone(),
                 Connection::new(stream),
                 self.base.auth.clone(),
                 self.base.climit.clone(),
+                self.base.max_pipeline,
                 self.base.signal.subscribe(),
                 self.base.terminate_tx.clone(),
             );
+            sslhandle.set_rate_limit(self.base.ratelimit.for_connection());
+            // log in as the first name in the certificate that is a user
+            if let Some(user) = peer_names
+                .iter()
+                .find(|name| sslhandle.authenticate_as(name.as_bytes()))
+            {
+                log::info!("Authenticated TLS client as `{user}` using its certificate");
+            }
             tokio::spawn(async move {
                 if let Err(e) = sslhandle.run().await {
                     log::error!("Error: {}", e);
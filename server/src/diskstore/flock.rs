@@ -108,7 +108,8 @@ impl FileLock {
     pub fn fsync(&self) -> Result<()> {
         self.file.sync_all()
     }
-    #[cfg(test)]
+    /// Duplicate the handle to the locked file. The duplicate shares the lock, so it must not be
+    /// unlocked while the original is still in use
     pub fn try_clone(&self) -> Result<Self> {
         Ok(FileLock {
             file: __sys::duplicate(&self.file)?,
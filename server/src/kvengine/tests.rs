@@ -109,3 +109,179 @@ fn test_encoder_validate_with_non_unicode() {
     let encoder = tbl.get_double_encoder();
     assert!(!encoder("hello".as_bytes(), b"Hello \xF0\x90\x80World"));
 }
+
+mod concurrency {
+    //! Randomized concurrent workloads against a single table, checked against an oracle
+    use {
+        super::{KVEStandard, SharedSlice},
+        rand::{rngs::StdRng, Rng, SeedableRng},
+        std::{
+            collections::HashMap,
+            sync::{Arc, Barrier},
+            thread,
+        },
+    };
+
+    #[cfg(not(feature = "nightly"))]
+    const THREADS: usize = 8;
+    #[cfg(feature = "nightly")]
+    const THREADS: usize = 32;
+    #[cfg(not(feature = "nightly"))]
+    const OPS_PER_THREAD: usize = 10_000;
+    #[cfg(feature = "nightly")]
+    const OPS_PER_THREAD: usize = 200_000;
+    /// The number of keys that every thread owns (and can hence predict the state of)
+    const OWNED_KEYS: usize = 64;
+    /// The number of keys that all threads fight over
+    const CONTENDED_KEYS: usize = 256;
+
+    fn owned_key(thread: usize, key: usize) -> SharedSlice {
+        SharedSlice::from(format!("owned-{thread}-{key}"))
+    }
+
+    fn contended_key(key: usize) -> SharedSlice {
+        SharedSlice::from(format!("contended-{key}"))
+    }
+
+    /// Runs `f` on [`THREADS`] threads (with a per-thread seeded RNG) that start together,
+    /// returning the results in thread order
+    fn run_workload<T, F>(f: F) -> Vec<T>
+    where
+        T: Send + 'static,
+        F: Fn(usize, &mut StdRng) -> T + Send + Sync + 'static,
+    {
+        let seed: u64 = rand::thread_rng().gen();
+        let f = Arc::new(f);
+        let barrier = Arc::new(Barrier::new(THREADS));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|id| {
+                let f = f.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(id as u64));
+                    barrier.wait();
+                    f(id, &mut rng)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or_else(|_| panic!("workload failed (seed: {seed})"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn owned_keys_match_oracle() {
+        // every thread mutates its own keys while all the other threads hammer the same table,
+        // so the table must always agree with the thread's own view of its keys
+        let tbl = Arc::new(KVEStandard::default());
+        let table = tbl.clone();
+        let oracles = run_workload(move |id, rng| {
+            let mut oracle: HashMap<SharedSlice, SharedSlice> = HashMap::new();
+            for op in 0..OPS_PER_THREAD {
+                let key = owned_key(id, rng.gen_range(0..OWNED_KEYS));
+                let val = SharedSlice::from(format!("{id}-{op}"));
+                match rng.gen_range(0..5) {
+                    0 => {
+                        let inserted = table.set(key.clone(), val.clone()).unwrap();
+                        assert_eq!(inserted, !oracle.contains_key(&key));
+                        oracle.entry(key).or_insert(val);
+                    }
+                    1 => {
+                        let updated = table.update(key.clone(), val.clone()).unwrap();
+                        assert_eq!(updated, oracle.contains_key(&key));
+                        if let Some(v) = oracle.get_mut(&key) {
+                            *v = val;
+                        }
+                    }
+                    2 => {
+                        table.upsert(key.clone(), val.clone()).unwrap();
+                        oracle.insert(key, val);
+                    }
+                    3 => {
+                        let removed = table.remove(&key).unwrap();
+                        assert_eq!(removed, oracle.remove(&key).is_some());
+                    }
+                    _ => {
+                        let got = table.get_cloned(&key).unwrap();
+                        assert_eq!(got.as_ref(), oracle.get(&key));
+                    }
+                }
+            }
+            oracle
+        });
+        let expected_len: usize = oracles.iter().map(HashMap::len).sum();
+        assert_eq!(tbl.len(), expected_len);
+        for oracle in oracles {
+            for (key, val) in oracle {
+                assert_eq!(tbl.get_cloned(&key).unwrap(), Some(val));
+            }
+        }
+    }
+
+    #[test]
+    fn contended_keys_no_lost_writes() {
+        // all threads race to insert and then remove the same keys. exactly one thread must win
+        // every insert and every remove; anything else means that a write was lost (or duplicated)
+        let tbl = Arc::new(KVEStandard::default());
+        let table = tbl.clone();
+        // no one starts removing until everyone is done inserting
+        let phase = Arc::new(Barrier::new(THREADS));
+        let wins = run_workload(move |id, rng| {
+            let mut inserts = 0usize;
+            let mut removes = 0usize;
+            let mut order: Vec<usize> = (0..CONTENDED_KEYS).collect();
+            // everyone visits the keys in a different order to maximize interleaving
+            for i in (1..order.len()).rev() {
+                order.swap(i, rng.gen_range(0..=i));
+            }
+            for &key in &order {
+                let val = SharedSlice::from(format!("{id}"));
+                if table.set(contended_key(key), val).unwrap() {
+                    inserts += 1;
+                }
+            }
+            phase.wait();
+            for &key in order.iter().rev() {
+                if table.remove(contended_key(key)).unwrap() {
+                    removes += 1;
+                }
+            }
+            (inserts, removes)
+        });
+        let (inserts, removes) = wins
+            .into_iter()
+            .fold((0, 0), |(i, r), (ni, nr)| (i + ni, r + nr));
+        assert_eq!(inserts, CONTENDED_KEYS);
+        assert_eq!(removes, CONTENDED_KEYS);
+        assert_eq!(tbl.len(), 0);
+    }
+
+    #[test]
+    fn contended_upserts_never_tear() {
+        // concurrent upserts to the same keys can be ordered arbitrarily, but readers must only
+        // ever see a value that some writer actually wrote for that key
+        let tbl = Arc::new(KVEStandard::default());
+        let table = tbl.clone();
+        run_workload(move |id, rng| {
+            for op in 0..OPS_PER_THREAD {
+                let key = rng.gen_range(0..CONTENDED_KEYS);
+                if rng.gen_bool(0.5) {
+                    let val = SharedSlice::from(format!("{key}:{id}:{op}"));
+                    table.upsert(contended_key(key), val).unwrap();
+                } else if let Some(val) = table.get_cloned(contended_key(key)).unwrap() {
+                    let val = String::from_utf8(val.to_vec()).unwrap();
+                    let mut parts = val.split(':');
+                    assert_eq!(parts.next(), Some(key.to_string().as_str()));
+                    let writer: usize = parts.next().unwrap().parse().unwrap();
+                    let writer_op: usize = parts.next().unwrap().parse().unwrap();
+                    assert!(writer < THREADS && writer_op < OPS_PER_THREAD);
+                }
+            }
+        });
+        assert!(tbl.len() <= CONTENDED_KEYS);
+    }
+}
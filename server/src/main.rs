@@ -103,8 +103,23 @@ fn main() {
     // check if any other process is using the data directory and lock it if not (else error)
     // important: create the pid_file just here and nowhere else because check_args can also
     // involve passing --help or wrong arguments which can falsely create a PID file
-    let pid_file = run_pre_startup_tasks();
-    let db = runtime.block_on(async move { arbiter::run(cfg, restore_file).await });
+    let pid_file = if cfg.ephemeral || cfg.conformance {
+        // we don't touch the data directory, so there's nothing to lock
+        None
+    } else {
+        Some(run_pre_startup_tasks())
+    };
+    let lease = match pid_file.as_ref().map(FileLock::try_clone).transpose() {
+        Ok(lease) => lease,
+        Err(e) => {
+            log::error!(
+                "Startup failure: Failed to duplicate the pid file handle: {}",
+                e
+            );
+            crate::exit_error();
+        }
+    };
+    let db = runtime.block_on(async move { arbiter::run(cfg, restore_file, lease).await });
     // Make sure all background workers terminate
     drop(runtime);
     let db = match db {
@@ -112,13 +127,16 @@ fn main() {
         Err(e) => {
             // uh oh, something happened while starting up
             log::error!("{}", e);
-            services::pre_shutdown_cleanup(pid_file, None);
+            if let Some(pid_file) = pid_file {
+                services::pre_shutdown_cleanup(pid_file, None);
+            }
             process::exit(1);
         }
     };
     log::info!("Stopped accepting incoming connections");
+    let ephemeral = pid_file.is_none();
     arbiter::finalize_shutdown(db, pid_file);
-    {
+    if !ephemeral {
         // remove this file in debug builds for harness to pick it up
         #[cfg(debug_assertions)]
         std::fs::remove_file(PID_FILE_PATH).unwrap();
@@ -130,6 +148,10 @@ fn main() {
 fn check_args_and_get_cfg() -> (ConfigurationSet, Option<String>) {
     match config::get_config() {
         Ok(cfg) => {
+            if let Some(task) = cfg.task() {
+                let ok = services::run_task(task, cfg.storage());
+                process::exit(if ok { 0 } else { 1 });
+            }
             if cfg.is_artful() {
                 log::info!("Skytable v{} | {}\n{}", VERSION, URL, TEXT);
             } else {
@@ -157,17 +179,21 @@ fn check_args_and_get_cfg() -> (ConfigurationSet, Option<String>) {
 /// the directory is in active use by another process. If the file doesn't then
 /// we're free to create our own file and write our own PID to it. Any subsequent
 /// processes will detect this and this helps us prevent two processes from writing
-/// to the same directory which can cause potentially undefined behavior.
+/// to the same directory which can cause potentially undefined behavior. Along with our PID, we
+/// also keep a lease in the file (see [`services::lease`]).
 ///
 fn run_pre_startup_tasks() -> FileLock {
     let mut file = match FileLock::lock(PID_FILE_PATH) {
         Ok(fle) => fle,
         Err(e) => {
-            log::error!("Startup failure: Failed to lock pid file: {}", e);
+            log::error!(
+                "Startup failure: Failed to lock pid file (see `skyd lock-status`): {}",
+                e
+            );
             crate::exit_error();
         }
     };
-    if let Err(e) = file.write(process::id().to_string().as_bytes()) {
+    if let Err(e) = services::lease::take(&mut file) {
         log::error!("Startup failure: Failed to write to pid file: {}", e);
         crate::exit_error();
     }
@@ -150,6 +150,32 @@ pub trait ProtocolSpec: Send + Sync {
     const RSTRING_LISTMAP_BAD_INDEX: &'static [u8];
     /// Respstring when a list is empty and we attempt to access/modify it
     const RSTRING_LISTMAP_LIST_IS_EMPTY: &'static [u8];
+    /// Respstring when writes are attempted to be resumed but they weren't paused
+    const RSTRING_NOT_QUIESCED: &'static [u8];
+    /// Respstring when data is attempted to be persisted while running in ephemeral mode
+    const RSTRING_EPHEMERAL: &'static [u8];
+    /// Respstring when a pipeline has more queries than allowed
+    const RSTRING_PIPELINE_TOO_LONG: &'static [u8];
+    /// Respstring when a write is attempted while the server is running in read-only mode
+    const RSTRING_READ_ONLY: &'static [u8];
+    /// Respstring when a write is attempted while writes are paused
+    const RSTRING_QUIESCED: &'static [u8];
+    /// Respstring when a capture is attempted to be started while another one is active
+    const RSTRING_CAPTURE_ACTIVE: &'static [u8];
+    /// Respstring when a capture is attempted to be started with the name of an existing capture
+    const RSTRING_CAPTURE_EXISTS: &'static [u8];
+    /// Respstring when a capture is attempted to be stopped but no capture is active
+    const RSTRING_NOT_CAPTURING: &'static [u8];
+    /// Respstring when a model that wasn't loaded on startup is attempted to be used
+    const RSTRING_MODEL_UNAVAILABLE: &'static [u8];
+    /// Respstring when a non-administrative action is run on the admin endpoint
+    const RSTRING_NOT_ADMIN_ACTION: &'static [u8];
+    /// Respstring when the backup to compare against doesn't exist
+    const RSTRING_BACKUP_NOT_FOUND: &'static [u8];
+    /// Respstring when the query rate limit for the connection or user has been exceeded
+    const RSTRING_RATE_LIMITED: &'static [u8];
+    /// Respstring when a write carries a fence token that is stale
+    const RSTRING_FENCED: &'static [u8];
 
     // element responses
     /// A string element containing the text "HEY!"
@@ -160,6 +186,8 @@ pub trait ProtocolSpec: Send + Sync {
     const FULLRESP_RCODE_PACKET_ERR: &'static [u8];
     /// A **full response** for a wrongtype error
     const FULLRESP_RCODE_WRONG_TYPE: &'static [u8];
+    /// A **full response** for a query that exceeds the configured limits
+    const FULLRESP_RSTRING_QUERY_TOO_LARGE: &'static [u8];
 
     // LUTs
     /// A LUT for SET operations
@@ -183,11 +211,12 @@ pub trait ProtocolSpec: Send + Sync {
         Self::RCODE_OKAY,
         Self::RCODE_NIL,
     );
-    const SKYHASH_PARSE_ERROR_LUT: [&'static [u8]; 4] = [
+    const SKYHASH_PARSE_ERROR_LUT: [&'static [u8]; 5] = [
         Self::FULLRESP_RCODE_PACKET_ERR,
         Self::FULLRESP_RCODE_PACKET_ERR,
         Self::FULLRESP_RCODE_WRONG_TYPE,
         Self::FULLRESP_RCODE_WRONG_TYPE,
+        Self::FULLRESP_RSTRING_QUERY_TOO_LARGE,
     ];
 
     // auth error respstrings
@@ -27,8 +27,11 @@
 #[cfg(test)]
 use self::interface::ProtocolSpec;
 use {
-    crate::corestore::heap_array::HeapArray,
-    core::{fmt, slice},
+    crate::{config::QueryLimits, corestore::heap_array::HeapArray, dbnet::QueryWithAdvance},
+    core::{
+        fmt, slice,
+        sync::atomic::{AtomicUsize, Ordering},
+    },
 };
 // pub mods
 pub mod interface;
@@ -112,11 +115,56 @@ pub enum ParseError {
     DatatypeParseFailure = 3u8,
     /// The client supplied the wrong query data type for the given query
     WrongType = 4u8,
+    /// The query exceeds the configured limits on the number of elements, the size of an
+    /// element or the size of the query
+    QueryTooLarge = 5u8,
 }
 
 /// A generic result to indicate parsing errors thorugh the [`ParseError`] enum
 pub type ParseResult<T> = Result<T, ParseError>;
 
+/*
+    The limits on queries are set once on startup (before we accept any connections) and are
+    read by the parsers for every packet
+*/
+static MAX_ELEMENTS: AtomicUsize = AtomicUsize::new(0);
+static MAX_ELEMENT_SIZE: AtomicUsize = AtomicUsize::new(0);
+static MAX_QUERY_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Set the limits imposed on all queries
+pub fn set_query_limits(limits: QueryLimits) {
+    MAX_ELEMENTS.store(limits.elements, Ordering::Release);
+    MAX_ELEMENT_SIZE.store(limits.element_size, Ordering::Release);
+    MAX_QUERY_SIZE.store(limits.query_size, Ordering::Release);
+}
+
+/// Returns the limits imposed on all queries
+fn query_limits() -> QueryLimits {
+    QueryLimits::new(
+        MAX_ELEMENTS.load(Ordering::Acquire),
+        MAX_ELEMENT_SIZE.load(Ordering::Acquire),
+        MAX_QUERY_SIZE.load(Ordering::Acquire),
+    )
+}
+
+/// Enforce the limit on the size of a query on the result of parsing `buf`, where `consumed`
+/// is the number of bytes that the parser consumed. If we need more data to complete the query
+/// but the buffer already exceeds the limit, there's no point in waiting for the rest of it
+fn check_query_size(
+    ret: ParseResult<Query>,
+    consumed: usize,
+    buf: &[u8],
+    limits: &QueryLimits,
+) -> ParseResult<QueryWithAdvance> {
+    let too_large = |size| limits.query_size != 0 && size > limits.query_size;
+    match ret {
+        Ok(query) if !too_large(consumed) => Ok((query, consumed)),
+        Err(ParseError::NotEnough) if !too_large(buf.len()) => Err(ParseError::NotEnough),
+        Ok(_) | Err(ParseError::NotEnough) => Err(ParseError::QueryTooLarge),
+        Err(e) => Err(e),
+    }
+}
+
 #[derive(Debug)]
 pub enum Query {
     Simple(SimpleQuery),
@@ -168,6 +216,9 @@ impl PipelinedQuery {
     pub fn into_inner(self) -> HeapArray<HeapArray<UnsafeSlice>> {
         self.data
     }
+    pub fn as_slice(&self) -> &[HeapArray<UnsafeSlice>] {
+        &self.data
+    }
     #[cfg(test)]
     fn into_owned(self) -> OwnedPipelinedQuery {
         OwnedPipelinedQuery {
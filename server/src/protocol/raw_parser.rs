@@ -148,6 +148,30 @@ pub(super) trait RawParserExt: RawParser + RawParserMeta {
             }
         }
     }
+    /// Attempt to read the number of elements (or queries) that follow, rejecting counts above
+    /// `limit` (`0` for no limit)
+    fn read_count(&mut self, limit: usize) -> ParseResult<usize> {
+        let count = self.read_usize()?;
+        if limit != 0 && count > limit {
+            Err(ParseError::QueryTooLarge)
+        } else if count > self.remaining() / 2 {
+            // every element needs atleast two bytes, so the packet is incomplete. we check
+            // this here so that we never allocate for elements the client hasn't sent
+            Err(ParseError::NotEnough)
+        } else {
+            Ok(count)
+        }
+    }
+    /// Attempt to read a length-prefixed element, rejecting sizes above `limit` (`0` for no
+    /// limit)
+    fn read_sized(&mut self, limit: usize) -> ParseResult<UnsafeSlice> {
+        let size = self.read_usize()?;
+        if limit != 0 && size > limit {
+            Err(ParseError::QueryTooLarge)
+        } else {
+            self.read_until(size)
+        }
+    }
     /// Attempt to read an `usize` from the buffer
     fn read_usize(&mut self) -> ParseResult<usize> {
         let line = self.read_line_pedantic()?;
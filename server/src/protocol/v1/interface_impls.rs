@@ -95,6 +95,19 @@ impl ProtocolSpec for Skyhash1 {
     const RSTRING_BAD_TYPE_FOR_KEY: &'static [u8] = eresp!("bad-type-for-key");
     const RSTRING_LISTMAP_BAD_INDEX: &'static [u8] = eresp!("bad-list-index");
     const RSTRING_LISTMAP_LIST_IS_EMPTY: &'static [u8] = eresp!("list-is-empty");
+    const RSTRING_NOT_QUIESCED: &'static [u8] = eresp!("not-quiesced");
+    const RSTRING_EPHEMERAL: &'static [u8] = eresp!("err-ephemeral");
+    const RSTRING_PIPELINE_TOO_LONG: &'static [u8] = eresp!("err-pipeline-too-long");
+    const RSTRING_READ_ONLY: &'static [u8] = eresp!("err-read-only");
+    const RSTRING_QUIESCED: &'static [u8] = eresp!("err-quiesced");
+    const RSTRING_CAPTURE_ACTIVE: &'static [u8] = eresp!("capture-active");
+    const RSTRING_CAPTURE_EXISTS: &'static [u8] = eresp!("capture-exists");
+    const RSTRING_NOT_CAPTURING: &'static [u8] = eresp!("not-capturing");
+    const RSTRING_MODEL_UNAVAILABLE: &'static [u8] = eresp!("model-unavailable");
+    const RSTRING_NOT_ADMIN_ACTION: &'static [u8] = eresp!("not-admin-action");
+    const RSTRING_BACKUP_NOT_FOUND: &'static [u8] = eresp!("backup-not-found");
+    const RSTRING_RATE_LIMITED: &'static [u8] = eresp!("rate-limited");
+    const RSTRING_FENCED: &'static [u8] = eresp!("err-fenced");
 
     // elements
     const ELEMRESP_HEYA: &'static [u8] = b"+4\nHEY!\n";
@@ -102,6 +115,7 @@ impl ProtocolSpec for Skyhash1 {
     // full responses
     const FULLRESP_RCODE_PACKET_ERR: &'static [u8] = b"*1\n!1\n4\n";
     const FULLRESP_RCODE_WRONG_TYPE: &'static [u8] = b"*1\n!1\n7\n";
+    const FULLRESP_RSTRING_QUERY_TOO_LARGE: &'static [u8] = b"*1\n!15\nquery-too-large\n";
 
     // auth rcodes/strings
     const AUTH_ERROR_ALREADYCLAIMED: &'static [u8] = eresp!("err-auth-already-claimed");
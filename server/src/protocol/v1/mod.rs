@@ -30,6 +30,7 @@ use {
         ParseError, ParseResult, PipelinedQuery, Query, SimpleQuery, UnsafeSlice,
     },
     crate::{
+        config::QueryLimits,
         corestore::heap_array::{HeapArray, HeapArrayWriter},
         dbnet::QueryWithAdvance,
     },
@@ -59,6 +60,7 @@ mod tests;
 pub struct Parser {
     end: *const u8,
     cursor: *const u8,
+    limits: QueryLimits,
 }
 
 unsafe impl RawParser for Parser {
@@ -83,6 +85,7 @@ impl Parser {
             Self {
                 end: slice.as_ptr().add(slice.len()),
                 cursor: slice.as_ptr(),
+                limits: QueryLimits::default(),
             }
         }
     }
@@ -112,8 +115,7 @@ impl Parser {
     }
     /// Gets the _next element. **The cursor should be at the tsymbol (passed)**
     fn _next(&mut self) -> ParseResult<UnsafeSlice> {
-        let element_size = self.read_usize()?;
-        self.read_until(element_size)
+        self.read_sized(self.limits.element_size)
     }
 }
 
@@ -158,7 +160,7 @@ impl Parser {
                 // UNSAFE(@ohsayan): Just checked length
                 self.incr_cursor();
             }
-            let query_count = self.read_usize()?;
+            let query_count = self.read_count(self.limits.elements)?;
             let mut writer = HeapArrayWriter::with_capacity(query_count);
             for i in 0..query_count {
                 unsafe {
@@ -223,7 +225,7 @@ impl Parser {
                 // UNSAFE(@ohsayan): Checked buffer len and incremented, so we're good
                 self.incr_cursor()
             };
-            let query_count = self.read_usize()?; // get the length
+            let query_count = self.read_count(0)?; // get the length
             if query_count == 1 {
                 Ok(Query::Simple(self.parse_simple_query()?))
             } else {
@@ -234,9 +236,13 @@ impl Parser {
         }
     }
     pub fn parse(buf: &[u8]) -> ParseResult<QueryWithAdvance> {
+        Self::parse_with_limits(buf, super::query_limits())
+    }
+    fn parse_with_limits(buf: &[u8], limits: QueryLimits) -> ParseResult<QueryWithAdvance> {
         let mut slf = Self::new(buf);
-        let body = slf._parse()?;
+        slf.limits = limits;
+        let body = slf._parse();
         let consumed = slf.cursor_ptr() as usize - buf.as_ptr() as usize;
-        Ok((body, consumed))
+        super::check_query_size(body, consumed, buf, &slf.limits)
     }
 }
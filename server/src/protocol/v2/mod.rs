@@ -31,7 +31,7 @@ use {
         raw_parser::{RawParser, RawParserExt, RawParserMeta},
         ParseError, ParseResult, PipelinedQuery, Query, SimpleQuery, UnsafeSlice,
     },
-    crate::{corestore::heap_array::HeapArray, dbnet::QueryWithAdvance},
+    crate::{config::QueryLimits, corestore::heap_array::HeapArray, dbnet::QueryWithAdvance},
 };
 
 #[cfg(feature = "nightly")]
@@ -44,6 +44,7 @@ mod tests;
 pub struct Parser {
     end: *const u8,
     cursor: *const u8,
+    limits: QueryLimits,
 }
 
 unsafe impl RawParser for Parser {
@@ -68,6 +69,7 @@ impl Parser {
             Self {
                 end: slice.as_ptr().add(slice.len()),
                 cursor: slice.as_ptr(),
+                limits: QueryLimits::default(),
             }
         }
     }
@@ -88,12 +90,11 @@ impl Parser {
     /// ...
     /// ```
     fn _next_simple_query(&mut self) -> ParseResult<HeapArray<UnsafeSlice>> {
-        let element_count = self.read_usize()?;
+        let element_count = self.read_count(self.limits.elements)?;
         unsafe {
             let mut data = HeapArray::new_writer(element_count);
             for i in 0..element_count {
-                let element_size = self.read_usize()?;
-                let element = self.read_until(element_size)?;
+                let element = self.read_sized(self.limits.element_size)?;
                 data.write_to_index(i, element);
             }
             Ok(data.finish())
@@ -142,7 +143,7 @@ impl Parser {
     /// x    -> Q2E2 itself
     /// ```
     fn next_pipeline(&mut self) -> ParseResult<PipelinedQuery> {
-        let query_count = self.read_usize()?;
+        let query_count = self.read_count(0)?;
         unsafe {
             let mut queries = HeapArray::new_writer(query_count);
             for i in 0..query_count {
@@ -179,9 +180,13 @@ impl Parser {
     // only expose this. don't expose Self::new since that'll be _relatively easier_ to
     // invalidate invariants for
     pub fn parse(buf: &[u8]) -> ParseResult<QueryWithAdvance> {
+        Self::parse_with_limits(buf, super::query_limits())
+    }
+    fn parse_with_limits(buf: &[u8], limits: QueryLimits) -> ParseResult<QueryWithAdvance> {
         let mut slf = Self::new(buf);
-        let body = slf._parse()?;
+        slf.limits = limits;
+        let body = slf._parse();
         let consumed = slf.cursor_ptr() as usize - buf.as_ptr() as usize;
-        Ok((body, consumed))
+        super::check_query_size(body, consumed, buf, &slf.limits)
     }
 }
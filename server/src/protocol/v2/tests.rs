@@ -29,7 +29,10 @@ use {
         super::raw_parser::{RawParser, RawParserExt, RawParserMeta},
         Parser, PipelinedQuery, Query, SimpleQuery,
     },
-    crate::protocol::{iter::AnyArrayIter, ParseError},
+    crate::{
+        config::QueryLimits,
+        protocol::{iter::AnyArrayIter, ParseError},
+    },
     std::{iter::Map, vec::IntoIter as VecIntoIter},
 };
 
@@ -629,6 +632,48 @@ fn pipelined_query_fail_because_not_enough() {
     }
 }
 
+#[test]
+fn parse_does_not_allocate_for_unsent_elements() {
+    // a tiny packet claiming to have a huge number of elements
+    let body = b"*99999999999999\n";
+    assert_eq!(Parser::parse(body).unwrap_err(), ParseError::NotEnough);
+    let body = b"$99999999999999\n";
+    assert_eq!(Parser::parse(body).unwrap_err(), ParseError::NotEnough);
+}
+
+#[test]
+fn parse_fail_because_query_limits() {
+    let body = b"*3\n3\nSET1\nx3\n100";
+    // too many elements
+    let limits = QueryLimits::new(2, 0, 0);
+    assert_eq!(
+        Parser::parse_with_limits(body, limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+    // an element that's too large
+    let limits = QueryLimits::new(0, 2, 0);
+    assert_eq!(
+        Parser::parse_with_limits(body, limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+    // a query that's too large
+    let limits = QueryLimits::new(0, 0, body.len() - 1);
+    assert_eq!(
+        Parser::parse_with_limits(body, limits).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+    // an incomplete query that's already too large
+    assert_eq!(
+        Parser::parse_with_limits(&body[..body.len() - 1], QueryLimits::new(0, 0, 8)).unwrap_err(),
+        ParseError::QueryTooLarge
+    );
+    // within all the limits
+    let limits = QueryLimits::new(3, 3, body.len());
+    let (query, skip) = Parser::parse_with_limits(body, limits).unwrap();
+    assert_eq!(skip, body.len());
+    assert_eq!(simple_query(query).into_owned().data, v!["SET", "x", "100"]);
+}
+
 #[test]
 fn test_iter() {
     use super::{Parser, Query};
@@ -30,13 +30,86 @@ use crate::{
     actions::{self, ActionError, ActionResult},
     admin, auth, blueql,
     corestore::Corestore,
-    dbnet::{prelude::*, BufferedSocketStream},
-    protocol::{iter::AnyArrayIter, PipelinedQuery, SimpleQuery, UnsafeSlice},
+    dbnet::{fence, prelude::*, BufferedSocketStream},
+    protocol::{iter::AnyArrayIter, PipelinedQuery, Query, SimpleQuery, UnsafeSlice},
+    registry,
+    services::{
+        history,
+        metrics::{self, QueryKind},
+    },
 };
 
 pub type ActionIter<'a> = AnyArrayIter<'a>;
 
 const ACTION_AUTH: &[u8] = b"auth";
+/// Actions that modify data and are hence refused when the server is read-only (or when the
+/// connection's fence is stale)
+const WRITE_ACTIONS: [&[u8]; 14] = [
+    b"SET", b"UPDATE", b"DEL", b"MSET", b"MUPDATE", b"SSET", b"SDEL", b"SUPDATE", b"FLUSHDB",
+    b"USET", b"POP", b"MPOP", b"LSET", b"LMOD",
+];
+
+/// Returns true if the stage runs one of the given actions
+fn is_one_of(stage: &[UnsafeSlice], actions: &[&[u8]]) -> bool {
+    stage.first().map_or(false, |action| {
+        // UNSAFE(@ohsayan): The presence of the connection guarantees that this
+        // won't suddenly become invalid
+        let action = unsafe { action.as_slice() };
+        actions
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(action))
+    })
+}
+
+/// Returns true if the stage runs an action that modifies data
+fn is_write_action(stage: &[UnsafeSlice]) -> bool {
+    self::is_one_of(stage, &WRITE_ACTIONS)
+}
+
+/// Actions that are accepted on the admin endpoint
+const ADMIN_ACTIONS: [&[u8]; 5] = [b"AUTH", b"HEYA", b"SYS", b"MKSNAP", b"WHEREAMI"];
+
+/// Returns true if every stage in the query runs an administrative action
+pub fn is_admin_query(query: &Query) -> bool {
+    let is_admin_stage = |stage: &[UnsafeSlice]| self::is_one_of(stage, &ADMIN_ACTIONS);
+    match query {
+        Query::Simple(q) => is_admin_stage(q.as_slice()),
+        Query::Pipelined(p) => p.as_slice().iter().all(|stage| is_admin_stage(stage)),
+    }
+}
+
+/// The BlueQL statements
+const BLUEQL_ACTIONS: [&[u8]; 5] = [b"CREATE", b"DROP", b"INSPECT", b"USE", b"CHECK"];
+
+/// Returns true if the stage runs a BlueQL statement. Statements are sent as a single element, so
+/// we only look at the first word
+fn is_blueql_statement(stage: &[UnsafeSlice]) -> bool {
+    stage.first().map_or(false, |statement| {
+        // UNSAFE(@ohsayan): The presence of the connection guarantees that this
+        // won't suddenly become invalid
+        let statement = unsafe { statement.as_slice() };
+        let keyword = statement
+            .split(|byte| byte.is_ascii_whitespace())
+            .find(|word| !word.is_empty())
+            .unwrap_or_default();
+        BLUEQL_ACTIONS
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(keyword))
+    })
+}
+
+/// Returns the kind of query run by the stage (for metrics)
+fn query_kind(stage: &[UnsafeSlice]) -> QueryKind {
+    if self::is_write_action(stage) {
+        QueryKind::Write
+    } else if self::is_one_of(stage, &ADMIN_ACTIONS) {
+        QueryKind::Admin
+    } else if self::is_blueql_statement(stage) {
+        QueryKind::BlueQL
+    } else {
+        QueryKind::Read
+    }
+}
 
 macro_rules! gen_constants_and_matches {
     (
@@ -105,6 +178,26 @@ async fn execute_stage<'a, P: ProtocolSpec, C: BufferedSocketStream>(
     auth: &mut AuthProviderHandle,
     buf: &[UnsafeSlice],
 ) -> ActionResult<()> {
+    if metrics::is_enabled() {
+        metrics::record_query(self::query_kind(buf));
+    }
+    if registry::is_read_only() && self::is_write_action(buf) {
+        return util::err(P::RSTRING_READ_ONLY);
+    }
+    // held until the action is done so that quiescing waits for this write
+    let _write = if self::is_write_action(buf) {
+        match registry::begin_write() {
+            Some(write) => Some(write),
+            None => return util::err(P::RSTRING_QUIESCED),
+        }
+    } else {
+        None
+    };
+    if let Some(token) = db.get_fence() {
+        if self::is_write_action(buf) && !fence::is_current(token) {
+            return util::err(P::RSTRING_FENCED);
+        }
+    }
     let mut iter = unsafe {
         // UNSAFE(@ohsayan): The presence of the connection guarantees that this
         // won't suddenly become invalid
@@ -137,6 +230,8 @@ async fn execute_stage<'a, P: ProtocolSpec, C: BufferedSocketStream>(
             LGET => actions::lists::lget::lget,
             LMOD => actions::lists::lmod::lmod,
             WHEREAMI => actions::whereami::whereami,
+            FENCE => actions::fence::fence,
+            CHECKSUM => actions::checksum::checksum,
             SYS => admin::sys::sys,
             {
                 // actions that need other arguments
@@ -161,7 +256,10 @@ async fn execute_stage_pedantic<'a, C: BufferedSocketStream, P: ProtocolSpec>(
     };
     match ret.await {
         Ok(()) => Ok(()),
-        Err(ActionError::ActionError(e)) => con._write_raw(e).await,
+        Err(ActionError::ActionError(e)) => {
+            history::record_error();
+            con._write_raw(e).await
+        }
         Err(ActionError::IoError(ioe)) => Err(ioe),
     }
 }
@@ -176,6 +274,8 @@ action! {
     ) {
         for stage in pipeline.into_inner().iter() {
             self::execute_stage_pedantic(handle, con, auth, stage).await?;
+            // let other connections run so that a large pipeline doesn't hog this worker
+            tokio::task::yield_now().await;
         }
         Ok(())
     }
@@ -30,8 +30,18 @@
 //!
 
 use {
-    crate::corestore::lock::{QLGuard, QuickLock},
-    core::sync::atomic::{AtomicBool, Ordering},
+    crate::corestore::{
+        lazy::Lazy,
+        lock::{QLGuard, QuickLock},
+    },
+    core::{
+        sync::atomic::{self, AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        time::Duration,
+    },
+    std::{
+        thread,
+        time::{Instant, SystemTime, UNIX_EPOCH},
+    },
 };
 
 const ORD_ACQ: Ordering = Ordering::Acquire;
@@ -81,18 +91,151 @@ static FLUSH_STATE: QuickLock<()> = QuickLock::new(());
 /// The preload trip switch
 static PRELOAD_TRIPSWITCH: Trip = Trip::new_untripped();
 static CLEANUP_TRIPSWITCH: Trip = Trip::new_untripped();
+/// The data load trip switch
+static LOADED_TRIPSWITCH: Trip = Trip::new_untripped();
+/// Whether the server is running in ephemeral (in-memory only) mode
+static EPHEMERAL: AtomicBool = AtomicBool::new(false);
+/// Whether the server is running in read-only mode
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+/// The time (in milliseconds since [`MONOTONIC_BASE`]) until which writes are paused. `0`
+/// indicates that writes aren't paused
+static QUIESCE_DEADLINE: AtomicU64 = AtomicU64::new(0);
+/// The number of writes that got past the quiesce check and haven't finished yet
+static INFLIGHT_WRITES: AtomicUsize = AtomicUsize::new(0);
+/// The base for deadlines that shouldn't be affected by changes to the system clock
+static MONOTONIC_BASE: Lazy<Instant, fn() -> Instant> = Lazy::new(Instant::now);
+/// The time (in milliseconds since the UNIX epoch) at which data was last successfully written
+/// to disk. `0` indicates that nothing has been written since startup
+static LAST_SAVE: AtomicU64 = AtomicU64::new(0);
 
-/// Check the global system state
+/// Check if writes can be accepted, i.e the global system state is healthy, the server isn't
+/// read-only and writes haven't been paused
 pub fn state_okay() -> bool {
+    self::health_okay() && !self::is_read_only() && !self::is_quiesced()
+}
+
+/// Check the global system health
+pub fn health_okay() -> bool {
     GLOBAL_STATE.load(ORD_ACQ)
 }
 
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Returns the number of milliseconds since [`MONOTONIC_BASE`] (this never goes backwards)
+fn monotonic_millis() -> u64 {
+    MONOTONIC_BASE.elapsed().as_millis() as u64
+}
+
+/// Pause all writes until [`end_quiesce`] is called or until `timeout` elapses (whichever
+/// happens first). Writes that had already started may still be running when this returns, so
+/// call [`wait_for_writes`] before relying on the data not changing
+pub fn begin_quiesce(timeout: Duration) {
+    // the deadline is never 0 (which would mean that writes aren't paused)
+    let deadline = monotonic_millis()
+        .saturating_add(timeout.as_millis() as u64)
+        .max(1);
+    QUIESCE_DEADLINE.store(deadline, ORD_SEQ)
+}
+
+/// Resume writes. Returns false if writes weren't paused (or if the pause had already timed out)
+pub fn end_quiesce() -> bool {
+    let deadline = QUIESCE_DEADLINE.swap(0, ORD_SEQ);
+    deadline != 0 && monotonic_millis() < deadline
+}
+
+/// Check if writes have been paused
+pub fn is_quiesced() -> bool {
+    let deadline = QUIESCE_DEADLINE.load(ORD_ACQ);
+    if deadline == 0 {
+        return false;
+    }
+    if monotonic_millis() < deadline {
+        true
+    } else {
+        // the pause timed out; only one thread gets to reset it (and log it)
+        if QUIESCE_DEADLINE
+            .compare_exchange(deadline, 0, ORD_SEQ, ORD_SEQ)
+            .is_ok()
+        {
+            log::warn!("Quiesce timed out. Resuming writes");
+        }
+        false
+    }
+}
+
+/// An in-flight write. Writes aren't considered done until this is dropped
+pub struct WriteGuard {
+    _private: (),
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        INFLIGHT_WRITES.fetch_sub(1, ORD_SEQ);
+    }
+}
+
+/// Register a write, returning `None` if writes have been paused. The returned guard should be
+/// held until the write is done so that [`wait_for_writes`] can wait for it
+pub fn begin_write() -> Option<WriteGuard> {
+    INFLIGHT_WRITES.fetch_add(1, ORD_SEQ);
+    let guard = WriteGuard { _private: () };
+    // pairs with the store in `begin_quiesce`: either we see the pause, or the quiescer sees us
+    atomic::fence(ORD_SEQ);
+    if self::is_quiesced() {
+        None
+    } else {
+        Some(guard)
+    }
+}
+
+/// Block until every write that started before [`begin_quiesce`] is done. Returns false if the
+/// pause ended (or timed out) before that happened
+pub fn wait_for_writes() -> bool {
+    atomic::fence(ORD_SEQ);
+    while INFLIGHT_WRITES.load(ORD_SEQ) != 0 {
+        if !self::is_quiesced() {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    self::is_quiesced()
+}
+
+/// Record that all data (or a single model) was just successfully written to disk
+pub fn record_save() {
+    LAST_SAVE.store(now_millis(), ORD_REL)
+}
+
+/// Returns the number of seconds since data was last successfully written to disk, if anything
+/// has been written since startup
+pub fn secs_since_last_save() -> Option<u64> {
+    match LAST_SAVE.load(ORD_ACQ) {
+        0 => None,
+        last => Some(now_millis().saturating_sub(last) / 1000),
+    }
+}
+
 /// Lock the global flush state. **Remember to drop the lock guard**; else you'll
 /// end up pausing all sorts of global flushing/transactional systems
 pub fn lock_flush_state() -> QLGuard<'static, ()> {
     FLUSH_STATE.lock()
 }
 
+/// Mark the server as running in ephemeral mode. **Must be called before any data is loaded**
+pub fn set_ephemeral() {
+    EPHEMERAL.store(true, ORD_SEQ)
+}
+
+/// Check if the server is running in ephemeral mode, i.e nothing should ever be written to disk
+pub fn is_ephemeral() -> bool {
+    EPHEMERAL.load(ORD_ACQ)
+}
+
 /// Poison the global system state
 pub fn poison() {
     GLOBAL_STATE.store(false, ORD_REL)
@@ -112,3 +255,19 @@ pub fn get_preload_tripswitch() -> &'static Trip {
 pub fn get_cleanup_tripswitch() -> &'static Trip {
     &CLEANUP_TRIPSWITCH
 }
+
+/// Get a static reference to the global data load trip switch. This is tripped once all the
+/// data has been restored
+pub fn get_loaded_tripswitch() -> &'static Trip {
+    &LOADED_TRIPSWITCH
+}
+
+/// Mark the server as read-only. **Must be called before any connections are accepted**
+pub fn set_read_only() {
+    READ_ONLY.store(true, ORD_SEQ)
+}
+
+/// Check if the server is running in read-only mode, i.e all writes should be refused
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(ORD_ACQ)
+}
@@ -27,11 +27,16 @@
 use {
     crate::{
         config::BGSave,
-        corestore::Corestore,
+        corestore::{memstore::ObjectID, table::Table, Corestore},
         registry,
-        storage::{self, v1::flush::Autoflush},
+        services::metrics,
+        storage::{
+            self,
+            v1::flush::{self, Autoflush},
+        },
         IoResult,
     },
+    std::io::{Error as IoError, ErrorKind},
     tokio::{
         sync::broadcast::Receiver,
         time::{self, Duration},
@@ -53,6 +58,12 @@ pub async fn bgsave_scheduler(handle: Corestore, bgsave_cfg: BGSave, mut termina
                 tokio::select! {
                     // Sleep until `duration` from the current time instant
                     _ = time::sleep_until(time::Instant::now() + duration) => {
+                        if registry::is_quiesced() {
+                            // the data on disk is already consistent and someone is probably
+                            // snapshotting it; don't touch it
+                            log::info!("Skipping BGSAVE because writes are paused");
+                            continue;
+                        }
                         let cloned_handle = handle.clone();
                         // we spawn this process just to ensure that it doesn't block the runtime's workers
                         // dedicated to async tasks (non-blocking)
@@ -80,16 +91,65 @@ pub async fn bgsave_scheduler(handle: Corestore, bgsave_cfg: BGSave, mut termina
 ///
 /// This function just hides away the BGSAVE blocking section from the _public API_
 pub fn run_bgsave(handle: &Corestore) -> IoResult<()> {
+    self::flush_unless_ephemeral(handle, registry::is_ephemeral())
+}
+
+/// Flush all data unless `ephemeral` is set
+fn flush_unless_ephemeral(handle: &Corestore, ephemeral: bool) -> IoResult<()> {
+    if ephemeral {
+        // never overwrite the data directory with an in-memory store
+        return Err(IoError::new(
+            ErrorKind::Other,
+            "refusing to flush since we're running in ephemeral mode",
+        ));
+    }
     storage::v1::flush::flush_full(Autoflush, handle.get_store())
 }
 
+/// Flush a single table along with the partmap of its keyspace
+///
+/// If keyspaces or tables were created since the last flush, the directory tree and `PRELOAD`
+/// are outdated and hence a full BGSAVE is run instead
+pub fn flush_table_blocking(
+    handle: Corestore,
+    ksid: &ObjectID,
+    tblid: &ObjectID,
+    table: &Table,
+) -> bool {
+    // don't race with a BGSAVE (or another flush) writing the same files
+    let flush_lock = registry::lock_flush_state();
+    if registry::get_preload_tripswitch().is_tripped() {
+        // the flush lock isn't reentrant
+        drop(flush_lock);
+        return bgsave_blocking_section(handle);
+    }
+    let ret = match handle.get_keyspace(ksid) {
+        Some(ks) => flush::oneshot::flush_partmap(&Autoflush, ksid, ks.as_ref())
+            .and_then(|_| flush::oneshot::flush_table(&Autoflush, tblid, ksid, table)),
+        // the keyspace was dropped in the meantime, so there's nothing to flush
+        None => Ok(()),
+    };
+    drop(flush_lock);
+    match ret {
+        Ok(()) => {
+            registry::record_save();
+            true
+        }
+        Err(e) => {
+            log::error!("Failed to flush table with error: {e}");
+            false
+        }
+    }
+}
+
 /// This just wraps around [`_bgsave_blocking_section`] and prints nice log messages depending on the outcome
-fn bgsave_blocking_section(handle: Corestore) -> bool {
-    registry::lock_flush_state();
-    match run_bgsave(&handle) {
+pub fn bgsave_blocking_section(handle: Corestore) -> bool {
+    let _flush_lock = registry::lock_flush_state();
+    let okay = match run_bgsave(&handle) {
         Ok(_) => {
             log::info!("BGSAVE completed successfully");
             registry::unpoison();
+            registry::record_save();
             true
         }
         Err(e) => {
@@ -97,5 +157,26 @@ fn bgsave_blocking_section(handle: Corestore) -> bool {
             registry::poison();
             false
         }
+    };
+    metrics::record_bgsave(okay);
+    okay
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::flush_unless_ephemeral,
+        crate::{corestore::Corestore, storage::v1::sengine::SnapshotEngine},
+        std::{io::ErrorKind, sync::Arc},
+    };
+
+    #[test]
+    fn bgsave_refused_when_ephemeral() {
+        let db = Corestore::init_ephemeral(Arc::new(SnapshotEngine::new_disabled()));
+        // the guard runs before anything is written to the data directory
+        assert_eq!(
+            flush_unless_ephemeral(&db, true).unwrap_err().kind(),
+            ErrorKind::Other
+        );
     }
 }
@@ -0,0 +1,174 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Export
+//!
+//! `skyd export` writes every row in a model to a CSV or JSON lines file (in the same layout
+//! that `skyd import` reads), which makes it easy to move the data to another system. Just like
+//! an import, the data directory is locked for the duration of the export, so this can't run
+//! alongside the server
+
+use {
+    super::import::{self, Format, ImportResult, JsonRow, Value, PROGRESS_EVERY},
+    crate::{
+        config::{ExportTask, StorageSettings},
+        corestore::{table::DataModel, SharedSlice},
+    },
+    std::{
+        fs::File,
+        io::{self, BufWriter, Write},
+    },
+};
+
+/// Quote a CSV field if it needs to be quoted
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn utf8(bytes: &SharedSlice) -> ImportResult<String> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| "a row isn't valid unicode, so it can't be exported".to_owned())
+}
+
+/// Format a row (with exactly one value for a key/value model) as a line
+fn format_row(format: Format, key: String, value: Value) -> ImportResult<String> {
+    match format {
+        Format::Csv => {
+            let mut line = self::csv_field(&key);
+            let values = match value {
+                Value::Str(value) => vec![value],
+                Value::List(values) => values,
+            };
+            for value in values {
+                line.push(',');
+                line.push_str(&self::csv_field(&value));
+            }
+            Ok(line)
+        }
+        Format::Jsonl => serde_json::to_string(&JsonRow { key, value }).map_err(|e| e.to_string()),
+    }
+}
+
+fn export(task: &ExportTask, settings: &StorageSettings) -> ImportResult<()> {
+    let format = match (task.format.as_deref(), task.out.as_deref()) {
+        (None, None) => return Err("use `--format` to set the format of the output".to_owned()),
+        (format, out) => Format::new(format, out.unwrap_or_default())?,
+    };
+    import::with_data_dir(|| self::export_model(task, format, settings))
+}
+
+fn export_model(task: &ExportTask, format: Format, settings: &StorageSettings) -> ImportResult<()> {
+    let (_, _, table) = import::open_model(&task.model, settings)?;
+    if table.is_volatile() {
+        return Err(format!("`{}` is volatile, so it has no data", task.model));
+    }
+    let mut out: BufWriter<Box<dyn Write>> = match task.out.as_deref() {
+        Some(out) => BufWriter::new(Box::new(
+            File::create(out).map_err(|e| format!("failed to create `{out}`: {e}"))?,
+        )),
+        None => BufWriter::new(Box::new(io::stdout())),
+    };
+    let mut rows = 0;
+    let mut write_row = |key: String, value: Value| -> ImportResult<()> {
+        let line = self::format_row(format, key, value)?;
+        writeln!(out, "{line}").map_err(|e| format!("failed to write a row: {e}"))?;
+        rows += 1;
+        if rows % PROGRESS_EVERY == 0 {
+            log::info!("Exported {rows} rows");
+        }
+        Ok(())
+    };
+    match table.get_model_ref() {
+        DataModel::KV(kve) => {
+            for row in kve.get_inner_ref().iter() {
+                let key = self::utf8(row.key())?;
+                let value = self::utf8(row.value())?;
+                write_row(key, Value::Str(value))?;
+            }
+        }
+        DataModel::KVExtListmap(listmap) => {
+            for row in listmap.get_inner_ref().iter() {
+                let key = self::utf8(row.key())?;
+                let values = row
+                    .value()
+                    .read()
+                    .iter()
+                    .map(self::utf8)
+                    .collect::<ImportResult<Vec<_>>>()?;
+                write_row(key, Value::List(values))?;
+            }
+        }
+        DataModel::Unavailable(_) => {
+            return Err(format!("the data of `{}` wasn't loaded", task.model))
+        }
+    }
+    out.flush()
+        .map_err(|e| format!("failed to write a row: {e}"))?;
+    log::info!("Exported {rows} rows from `{}`", task.model);
+    Ok(())
+}
+
+/// Run the export. Returns true if the export succeeded
+pub fn run(task: &ExportTask, settings: &StorageSettings) -> bool {
+    match self::export(task, settings) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("Export failed: {e}");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_row, Format, Value};
+
+    #[test]
+    fn export_rows() {
+        let row = |key: &str, value| format_row(Format::Csv, key.to_owned(), value).unwrap();
+        assert_eq!(row("a", Value::Str("b".to_owned())), "a,b");
+        assert_eq!(
+            row(
+                "a,b",
+                Value::List(vec!["c\"d".to_owned(), "e\nf".to_owned()])
+            ),
+            "\"a,b\",\"c\"\"d\",\"e\nf\""
+        );
+        assert_eq!(
+            format_row(
+                Format::Jsonl,
+                "a".to_owned(),
+                Value::List(vec!["b".to_owned()])
+            )
+            .unwrap(),
+            r#"{"key":"a","value":["b"]}"#
+        );
+    }
+}
@@ -0,0 +1,157 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Health probe
+//!
+//! A minimal HTTP endpoint for orchestrators (like Kubernetes) that don't speak Skyhash. It
+//! doesn't need authentication and serves two routes:
+//! - `/livez`: Returns `200` as long as the process is up
+//! - `/readyz`: Returns `200` once all the data has been restored and the server is accepting
+//! writes (i.e it isn't poisoned because of failing disk writes). Otherwise, it returns `503`
+
+use {
+    crate::{
+        registry,
+        util::error::{Error, SkyResult},
+    },
+    std::net::IpAddr,
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        sync::broadcast::Receiver,
+        time::{self, Duration},
+    },
+};
+
+/// The maximum size of a request that we'll read
+pub(super) const REQUEST_BUFFER_SIZE: usize = 1024;
+/// The time after which we give up on a client that doesn't send a request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+const RESP_OKAY: &[u8] =
+    b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 3\r\nConnection: close\r\n\r\nok\n";
+const RESP_NOT_READY: &[u8] = b"HTTP/1.1 503 Service Unavailable\r\nContent-Type: text/plain\r\nContent-Length: 10\r\nConnection: close\r\n\r\nnot ready\n";
+pub(super) const RESP_NOT_FOUND: &[u8] = b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 10\r\nConnection: close\r\n\r\nnot found\n";
+pub(super) const RESP_BAD_REQUEST: &[u8] = b"HTTP/1.1 400 Bad Request\r\nContent-Type: text/plain\r\nContent-Length: 12\r\nConnection: close\r\n\r\nbad request\n";
+
+/// Bind the health probe to the given host and port
+pub async fn bind(host: IpAddr, port: u16) -> SkyResult<TcpListener> {
+    let listener = TcpListener::bind((host, port))
+        .await
+        .map_err(|e| Error::ioerror_extra(e, format!("binding health probe to port {port}")))?;
+    log::info!("Health probe listening on {host}:{port}");
+    Ok(listener)
+}
+
+/// The health probe service
+///
+/// This keeps answering probes until [`dbnet::run`] broadcasts a termination signal
+pub async fn health_service(listener: TcpListener, mut termination_signal: Receiver<()>) {
+    loop {
+        tokio::select! {
+            ret = listener.accept() => {
+                match ret {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_probe(stream));
+                    }
+                    Err(e) => log::warn!("Health probe failed to accept connection: {e}"),
+                }
+            }
+            _ = termination_signal.recv() => break,
+        }
+    }
+    log::info!("Health probe has exited");
+}
+
+async fn handle_probe(mut stream: TcpStream) {
+    let mut buf = [0u8; REQUEST_BUFFER_SIZE];
+    if let Some(request_line) = read_request_line(&mut stream, &mut buf).await {
+        let resp = respond(request_line);
+        let _ = stream.write_all(resp).await;
+        let _ = stream.shutdown().await;
+    }
+}
+
+/// Read the request line of an HTTP request (we don't care about the rest). Returns `None` if
+/// the client went away or sent a request that's too large (in which case we've already
+/// responded)
+pub(super) async fn read_request_line<'a>(
+    stream: &mut TcpStream,
+    buf: &'a mut [u8; REQUEST_BUFFER_SIZE],
+) -> Option<&'a [u8]> {
+    let mut read = 0;
+    loop {
+        match time::timeout(REQUEST_TIMEOUT, stream.read(&mut buf[read..])).await {
+            Ok(Ok(0)) | Ok(Err(_)) | Err(_) => return None,
+            Ok(Ok(n)) => read += n,
+        }
+        if let Some(pos) = buf[..read].windows(2).position(|w| w == b"\r\n") {
+            return Some(&buf[..pos]);
+        }
+        if read == REQUEST_BUFFER_SIZE {
+            let _ = stream.write_all(RESP_BAD_REQUEST).await;
+            return None;
+        }
+    }
+}
+
+/// Returns the path if the given HTTP request line is a `GET` request
+pub(super) fn get_path(request_line: &[u8]) -> Option<&[u8]> {
+    let mut parts = request_line.split(|b| *b == b' ');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(b"GET"), Some(path), Some(version)) if version.starts_with(b"HTTP/") => Some(path),
+        _ => None,
+    }
+}
+
+/// Returns the response for the given HTTP request line
+fn respond(request_line: &[u8]) -> &'static [u8] {
+    let path = match get_path(request_line) {
+        Some(path) => path,
+        None => return RESP_BAD_REQUEST,
+    };
+    match path {
+        b"/livez" => RESP_OKAY,
+        b"/readyz" => {
+            if registry::get_loaded_tripswitch().is_tripped() && registry::health_okay() {
+                RESP_OKAY
+            } else {
+                RESP_NOT_READY
+            }
+        }
+        _ => RESP_NOT_FOUND,
+    }
+}
+
+#[test]
+fn test_respond() {
+    assert_eq!(respond(b"GET /livez HTTP/1.1"), RESP_OKAY);
+    assert_eq!(respond(b"GET /livez HTTP/1.0"), RESP_OKAY);
+    assert_eq!(respond(b"GET /metrics HTTP/1.1"), RESP_NOT_FOUND);
+    assert_eq!(respond(b"POST /livez HTTP/1.1"), RESP_BAD_REQUEST);
+    assert_eq!(respond(b"GET /livez"), RESP_BAD_REQUEST);
+    assert_eq!(respond(b""), RESP_BAD_REQUEST);
+}
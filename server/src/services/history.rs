@@ -0,0 +1,187 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Metrics history
+//!
+//! We keep per-minute aggregates (queries per second, an approximate p99 latency, the number of
+//! errors and the number of bytes received) for the last [`HISTORY_MINUTES`] minutes in memory,
+//! so that `SYS REPORT HISTORY` can tell an operator what the server was doing an hour ago, even
+//! if no external monitoring has been set up. Nothing here is ever written to disk
+
+use {
+    chrono::{TimeZone, Utc},
+    core::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    },
+    parking_lot::Mutex,
+    std::{
+        collections::VecDeque,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    tokio::{sync::broadcast::Receiver, time},
+};
+
+/// The number of minutes that we keep aggregates for
+const HISTORY_MINUTES: usize = 24 * 60;
+/// The number of latency buckets. Bucket `i` counts the queries that took less than `2^(i + 1)`
+/// microseconds (and at least `2^i` microseconds, except for the first bucket)
+const LATENCY_BUCKETS: usize = 32;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU64 = AtomicU64::new(0);
+static QUERIES: AtomicU64 = AtomicU64::new(0);
+static ERRORS: AtomicU64 = AtomicU64::new(0);
+static BYTES: AtomicU64 = AtomicU64::new(0);
+static LATENCY: [AtomicU64; LATENCY_BUCKETS] = [ZERO; LATENCY_BUCKETS];
+static HISTORY: Mutex<VecDeque<Minute>> = parking_lot::const_mutex(VecDeque::new());
+
+/// The aggregates for a minute
+#[derive(Debug, PartialEq, Eq)]
+struct Minute {
+    /// the start of this minute (in seconds since the UNIX epoch)
+    start: u64,
+    /// the number of seconds this minute spans (this is shorter for the first minute)
+    secs: u64,
+    queries: u64,
+    errors: u64,
+    bytes: u64,
+    /// the upper bound of the latency bucket with the 99th percentile (in microseconds)
+    p99: u64,
+}
+
+impl Minute {
+    fn describe(&self) -> String {
+        let start = match Utc.timestamp_opt(self.start as i64, 0).single() {
+            Some(start) => start.format("%Y-%m-%d %H:%M UTC").to_string(),
+            None => self.start.to_string(),
+        };
+        format!(
+            "{start}: {:.2} qps, p99 < {}us, {} errors, {} bytes received",
+            self.queries as f64 / self.secs.max(1) as f64,
+            self.p99,
+            self.errors,
+            self.bytes
+        )
+    }
+}
+
+/// Record a query (or pipeline) that took `latency` to run and was `bytes` long on the wire
+pub fn record_query(latency: Duration, bytes: usize, failed: bool) {
+    let micros = latency.as_micros().min(u64::MAX as u128) as u64;
+    // the number of significant bits is the bucket
+    let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+    LATENCY[bucket.min(LATENCY_BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    QUERIES.fetch_add(1, Ordering::Relaxed);
+    BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+    if failed {
+        ERRORS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Record a failed stage of a pipeline (the pipeline itself is recorded by [`record_query`])
+pub fn record_error() {
+    ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the upper bound (in microseconds) of the bucket holding the 99th percentile
+fn p99(buckets: &[u64]) -> u64 {
+    let total: u64 = buckets.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    let target = total - total / 100;
+    let mut seen = 0;
+    for (i, count) in buckets.iter().enumerate() {
+        seen += count;
+        if seen >= target {
+            return 1 << (i + 1);
+        }
+    }
+    1 << buckets.len()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Close the minute that started at `start`, resetting all the counters
+fn close_minute(start: u64, end: u64) {
+    let take = |counter: &AtomicU64| counter.swap(0, Ordering::Relaxed);
+    let buckets: Vec<u64> = LATENCY.iter().map(take).collect();
+    let minute = Minute {
+        start,
+        secs: end.saturating_sub(start),
+        queries: take(&QUERIES),
+        errors: take(&ERRORS),
+        bytes: take(&BYTES),
+        p99: self::p99(&buckets),
+    };
+    let mut history = HISTORY.lock();
+    if history.len() == HISTORY_MINUTES {
+        history.pop_front();
+    }
+    history.push_back(minute);
+}
+
+/// Describe every minute in the history (oldest first)
+pub fn report() -> Vec<String> {
+    HISTORY.lock().iter().map(Minute::describe).collect()
+}
+
+/// The history service
+///
+/// This closes a minute at the end of every minute on the wall clock until [`dbnet::run`]
+/// broadcasts a termination signal
+pub async fn history_service(mut termination_signal: Receiver<()>) {
+    let mut start = now_secs();
+    loop {
+        let next = (start / 60 + 1) * 60;
+        tokio::select! {
+            _ = time::sleep(Duration::from_secs(next.saturating_sub(now_secs()))) => {
+                let end = now_secs();
+                self::close_minute(start, end);
+                start = end;
+            }
+            _ = termination_signal.recv() => break,
+        }
+    }
+    log::info!("Metrics history service has exited");
+}
+
+#[test]
+fn test_p99() {
+    assert_eq!(p99(&[0; LATENCY_BUCKETS]), 0);
+    // everything below 2us
+    assert_eq!(p99(&[10, 0, 0]), 2);
+    // 99 fast queries and one slow one: the p99 is still fast
+    assert_eq!(p99(&[99, 0, 0, 1]), 2);
+    // 98 fast queries and two slow ones: the p99 is slow
+    assert_eq!(p99(&[98, 0, 0, 2]), 16);
+}
@@ -0,0 +1,468 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Import
+//!
+//! `skyd import` loads the rows in a CSV or JSON lines file straight into a model in the data
+//! directory, without going through the network. The data directory is locked for the duration
+//! of the import, so this can't run alongside the server.
+//!
+//! - In a CSV file, the first column of every row is the key. Key/value models take exactly one
+//! more column (the value), while list models take the remaining columns as the elements of the
+//! list. Fields can be quoted with `"` (and a `"` within a quoted field is escaped as `""`)
+//! - In a JSON lines file, every line is an object like `{"key": "k", "value": "v"}`, where
+//! `value` is an array of strings for list models
+//!
+//! The rows are only written to disk once every row has been validated, so a file with a bad
+//! row doesn't leave a partial import behind
+
+use {
+    crate::{
+        config::{ImportTask, StorageSettings},
+        corestore::{
+            memstore::ObjectID,
+            table::{DataModel, Table},
+            SharedSlice,
+        },
+        diskstore::flock::FileLock,
+        kvengine::LockedVec,
+        services::lease,
+        storage::v1::{
+            flush::{oneshot, Autoflush, StorageTarget},
+            unflush,
+        },
+        PID_FILE_PATH,
+    },
+    serde::{Deserialize, Serialize},
+    std::{
+        fs::File,
+        io::{BufRead, BufReader},
+        path::Path,
+        sync::Arc,
+    },
+};
+
+/// Log the progress after these many rows
+pub(super) const PROGRESS_EVERY: usize = 100_000;
+
+pub(super) type ImportResult<T> = Result<T, String>;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(super) enum Format {
+    Csv,
+    Jsonl,
+}
+
+impl Format {
+    /// Returns the format with the given name or (if there's no name) the format that matches
+    /// the extension of `file`
+    pub(super) fn new(format: Option<&str>, file: &str) -> ImportResult<Self> {
+        let format = match format {
+            Some(format) => format,
+            None => Path::new(file)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_default(),
+        };
+        match format {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            _ => Err(format!(
+                "can't tell the format of `{file}`. use `--format` to set it"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(super) enum Value {
+    Str(String),
+    List(Vec<String>),
+}
+
+/// A row in a JSON lines file
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct JsonRow {
+    pub(super) key: String,
+    pub(super) value: Value,
+}
+
+/// Lock the data directory (taking the lease on it) and run `f`. The lease is released and the
+/// directory is unlocked whatever `f` returns
+pub(super) fn with_data_dir<T>(f: impl FnOnce() -> ImportResult<T>) -> ImportResult<T> {
+    // don't create a fresh instance if we were pointed at the wrong directory
+    if unflush::is_new_instance().map_err(|e| e.to_string())? {
+        return Err("there's no data directory here (is this the right directory?)".to_owned());
+    }
+    // keep the server away from the data directory while we're using it
+    let mut pid_file = FileLock::lock(PID_FILE_PATH)
+        .map_err(|e| format!("failed to lock the data directory (is the server running?): {e}"))?;
+    let ret = match lease::take(&mut pid_file) {
+        Ok(()) => f(),
+        Err(e) => Err(format!(
+            "failed to take the lease on the data directory: {e}"
+        )),
+    };
+    let released = lease::release(&mut pid_file)
+        .and_then(|_| pid_file.unlock())
+        .map_err(|e| format!("failed to unlock the data directory: {e}"));
+    // an error from `f` is more interesting than one from unlocking
+    let ret = ret?;
+    released.map(|_| ret)
+}
+
+/// Load the model with the given `space.model` name. The data directory must be locked
+pub(super) fn open_model(
+    name: &str,
+    settings: &StorageSettings,
+) -> ImportResult<(ObjectID, ObjectID, Arc<Table>)> {
+    let (ksid, tblid) = match name.split_once('.') {
+        Some((space, model)) => (
+            ObjectID::try_from_slice(space),
+            ObjectID::try_from_slice(model),
+        ),
+        None => (None, None),
+    };
+    let (ksid, tblid) = match (ksid, tblid) {
+        (Some(ksid), Some(tblid)) => (ksid, tblid),
+        _ => return Err(format!("`{name}` isn't a valid `space.model`")),
+    };
+    let store = unflush::read_full(settings).map_err(|e| e.to_string())?;
+    let table = store
+        .get_keyspace_atomic_ref(&ksid)
+        .and_then(|ks| ks.get_table_atomic_ref(&tblid))
+        .ok_or_else(|| format!("the model `{name}` doesn't exist"))?;
+    if table.is_unavailable() {
+        return Err(format!("the data of `{name}` wasn't loaded"));
+    }
+    Ok((ksid, tblid, table))
+}
+
+/// Read the next record from a CSV file, returning the fields in it. A quoted field can span
+/// several lines, so `line` is advanced by the number of lines that were read
+fn read_csv_record(
+    reader: &mut impl BufRead,
+    line: &mut usize,
+) -> ImportResult<Option<Vec<String>>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut buf = String::new();
+    let mut quoted = false;
+    loop {
+        buf.clear();
+        let read = reader
+            .read_line(&mut buf)
+            .map_err(|e| format!("line {}: {e}", *line + 1))?;
+        if read == 0 {
+            if quoted {
+                return Err(format!("line {}: unterminated quoted field", *line));
+            }
+            // EOF
+            return Ok(None);
+        }
+        *line += 1;
+        let text = buf.trim_end_matches(|c| c == '\n' || c == '\r');
+        if !quoted && field.is_empty() && fields.is_empty() && text.is_empty() {
+            // skip blank lines
+            continue;
+        }
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                    }
+                }
+                '"' if field.is_empty() => quoted = true,
+                ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                c => field.push(c),
+            }
+        }
+        if quoted {
+            // the field continues on the next line
+            field.push('\n');
+        } else {
+            fields.push(field);
+            return Ok(Some(fields));
+        }
+    }
+}
+
+/// Turn a JSON line into a key and a value
+fn parse_json_row(text: &str) -> ImportResult<(String, Value)> {
+    let row: JsonRow = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    Ok((row.key, row.value))
+}
+
+/// Read every row in the file, passing the line number, key and value of every row to `f`
+fn for_each_row(
+    file: &str,
+    format: Format,
+    header: bool,
+    mut f: impl FnMut(usize, String, Value) -> ImportResult<()>,
+) -> ImportResult<()> {
+    let file = File::open(file).map_err(|e| format!("failed to open `{file}`: {e}"))?;
+    let mut reader = BufReader::new(file);
+    let mut line = 0;
+    match format {
+        Format::Csv => {
+            if header {
+                self::read_csv_record(&mut reader, &mut line)?;
+            }
+            loop {
+                let start = line + 1;
+                let mut fields = match self::read_csv_record(&mut reader, &mut line)? {
+                    Some(fields) => fields.into_iter(),
+                    None => break Ok(()),
+                };
+                let key = fields.next().unwrap_or_default();
+                f(start, key, Value::List(fields.collect()))?;
+            }
+        }
+        Format::Jsonl => {
+            let mut buf = String::new();
+            loop {
+                buf.clear();
+                let read = reader
+                    .read_line(&mut buf)
+                    .map_err(|e| format!("line {}: {e}", line + 1))?;
+                if read == 0 {
+                    break Ok(());
+                }
+                line += 1;
+                if buf.trim().is_empty() {
+                    continue;
+                }
+                let (key, value) =
+                    self::parse_json_row(&buf).map_err(|e| format!("line {line}: {e}"))?;
+                f(line, key, value)?;
+            }
+        }
+    }
+}
+
+/// Validate a row against the model and insert it (unless this is a dry run)
+fn import_row(
+    table: &Table,
+    format: Format,
+    key: String,
+    value: Value,
+    dry_run: bool,
+) -> ImportResult<()> {
+    match table.get_model_ref() {
+        DataModel::KV(kve) => {
+            let value = match (format, value) {
+                (_, Value::Str(value)) => value,
+                // a CSV row has the value in its only other column
+                (Format::Csv, Value::List(mut values)) if values.len() == 1 => {
+                    values.pop().unwrap()
+                }
+                (Format::Csv, Value::List(values)) => {
+                    return Err(format!("expected 2 columns, found {}", values.len() + 1))
+                }
+                (Format::Jsonl, Value::List(_)) => {
+                    return Err("expected a string value, found a list".to_owned())
+                }
+            };
+            if !(kve.is_key_ok(key.as_bytes()) && kve.is_val_ok(value.as_bytes())) {
+                return Err("the row doesn't match the encoding of the model".to_owned());
+            }
+            if !dry_run {
+                kve.upsert_unchecked(key.into(), value.into());
+            }
+        }
+        DataModel::KVExtListmap(listmap) => {
+            let values = match value {
+                Value::List(values) => values,
+                Value::Str(_) => return Err("expected a list value, found a string".to_owned()),
+            };
+            if !(listmap.is_key_ok(key.as_bytes())
+                && values.iter().all(|v| listmap.is_val_ok(v.as_bytes())))
+            {
+                return Err("the row doesn't match the encoding of the model".to_owned());
+            }
+            if !dry_run {
+                let values = values.into_iter().map(SharedSlice::from).collect();
+                listmap.upsert_unchecked(key.into(), LockedVec::new(values));
+            }
+        }
+        DataModel::Unavailable(_) => return Err("the data of the model wasn't loaded".to_owned()),
+    }
+    Ok(())
+}
+
+/// Load every row in the file into the model and flush it to `target`. The model is only
+/// flushed once every row has been validated
+fn import_into<T: StorageTarget>(
+    target: &T,
+    task: &ImportTask,
+    format: Format,
+    ksid: &ObjectID,
+    tblid: &ObjectID,
+    table: &Table,
+) -> ImportResult<()> {
+    if table.is_volatile() {
+        return Err(format!(
+            "`{}` is volatile, so the imported rows would never be saved",
+            task.model
+        ));
+    }
+    let (mut rows, mut bad_rows) = (0, 0);
+    self::for_each_row(&task.file, format, task.header, |line, key, value| {
+        match self::import_row(table, format, key, value, task.dry_run) {
+            Ok(()) => rows += 1,
+            Err(e) => {
+                bad_rows += 1;
+                log::error!("line {line}: {e}");
+            }
+        }
+        if (rows + bad_rows) % PROGRESS_EVERY == 0 {
+            log::info!("Processed {} rows", rows + bad_rows);
+        }
+        Ok(())
+    })?;
+    if bad_rows != 0 {
+        // nothing has been flushed yet, so the data on disk is untouched
+        return Err(format!(
+            "{bad_rows} of {} rows are invalid, so nothing was imported",
+            rows + bad_rows
+        ));
+    }
+    if task.dry_run {
+        log::info!("All {rows} rows are valid (this is a dry run, so nothing was imported)");
+    } else {
+        oneshot::flush_table(target, tblid, ksid, table)
+            .map_err(|e| format!("failed to flush `{}`: {e}", task.model))?;
+        log::info!("Imported {rows} rows into `{}`", task.model);
+    }
+    Ok(())
+}
+
+fn import(task: &ImportTask, settings: &StorageSettings) -> ImportResult<()> {
+    let format = Format::new(task.format.as_deref(), &task.file)?;
+    self::with_data_dir(|| {
+        let (ksid, tblid, table) = self::open_model(&task.model, settings)?;
+        self::import_into(&Autoflush, task, format, &ksid, &tblid, &table)
+    })
+}
+
+/// Run the import. Returns true if the import succeeded
+pub fn run(task: &ImportTask, settings: &StorageSettings) -> bool {
+    match self::import(task, settings) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("Import failed: {e}");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{import_into, parse_json_row, read_csv_record, Format, Value},
+        crate::{
+            config::ImportTask,
+            corestore::{memstore::ObjectID, table::Table},
+            storage::v1::flush::RemoteSnapshot,
+        },
+        std::{fs, path::Path},
+    };
+
+    #[test]
+    fn csv_records() {
+        let src = "a,b\n\n\"c,d\",\"e\"\"f\"\n\"g\nh\",i\r\n";
+        let mut reader = src.as_bytes();
+        let mut line = 0;
+        let mut records = vec![];
+        while let Some(record) = read_csv_record(&mut reader, &mut line).unwrap() {
+            records.push(record);
+        }
+        assert_eq!(
+            records,
+            vec![
+                vec!["a".to_owned(), "b".to_owned()],
+                vec!["c,d".to_owned(), "e\"f".to_owned()],
+                vec!["g\nh".to_owned(), "i".to_owned()],
+            ]
+        );
+        assert_eq!(line, 5);
+        let mut reader = "\"a,b".as_bytes();
+        assert!(read_csv_record(&mut reader, &mut 0).is_err());
+    }
+
+    #[test]
+    fn json_rows() {
+        assert_eq!(
+            parse_json_row(r#"{"key": "a", "value": "b"}"#).unwrap(),
+            ("a".to_owned(), Value::Str("b".to_owned()))
+        );
+        assert_eq!(
+            parse_json_row(r#"{"key": "a", "value": ["b", "c"]}"#).unwrap(),
+            (
+                "a".to_owned(),
+                Value::List(vec!["b".to_owned(), "c".to_owned()])
+            )
+        );
+        assert!(parse_json_row(r#"{"key": "a", "value": 1}"#).is_err());
+        assert!(parse_json_row(r#"{"key": "a", "value": "b", "ttl": 1}"#).is_err());
+    }
+
+    #[test]
+    fn bad_row_leaves_model_unflushed() {
+        let table = Table::new_default_kve();
+        let ksid = unsafe { ObjectID::from_slice("myimportks") };
+        let tblid = unsafe { ObjectID::from_slice("myimporttbl") };
+        let _ = fs::remove_dir_all("data/rsnap/importsnap");
+        fs::create_dir_all("data/rsnap/importsnap/myimportks").unwrap();
+        let target = RemoteSnapshot::new("importsnap");
+        let flushed = Path::new("data/rsnap/importsnap/myimportks/myimporttbl");
+        let task = |file: &str| ImportTask {
+            file: file.to_owned(),
+            model: "myimportks.myimporttbl".to_owned(),
+            format: None,
+            header: false,
+            dry_run: false,
+        };
+        // the second row is missing its value
+        fs::write("data/rsnap/importsnap/bad.csv", "a,b\nc\n").unwrap();
+        let task_bad = task("data/rsnap/importsnap/bad.csv");
+        assert!(import_into(&target, &task_bad, Format::Csv, &ksid, &tblid, &table).is_err());
+        assert!(!flushed.exists());
+        // once every row is valid, the model is flushed
+        fs::write("data/rsnap/importsnap/good.csv", "a,b\nc,d\n").unwrap();
+        let task_good = task("data/rsnap/importsnap/good.csv");
+        import_into(&target, &task_good, Format::Csv, &ksid, &tblid, &table).unwrap();
+        assert!(flushed.exists());
+        fs::remove_dir_all("data/rsnap/importsnap").unwrap();
+    }
+}
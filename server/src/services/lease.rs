@@ -0,0 +1,245 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # PID file leases
+//!
+//! The PID file is locked (with an advisory lock) for as long as the server is using the data
+//! directory, and it also holds a lease: the PID of the holder and the time of its last
+//! heartbeat, which is renewed every [`HEARTBEAT_INTERVAL`] seconds. A clean shutdown clears the
+//! lease. This lets orchestrators tell what happened to the last holder (see `skyd lock-status`):
+//! - If the file is locked, a live process holds the directory. If its lease has expired, the
+//!   process is alive but isn't making progress
+//! - If the file isn't locked but still has a lease, the holder didn't shut down cleanly. The next
+//!   start takes over the directory without any manual intervention
+//! - If the file is empty (or missing), the directory is free
+
+use {
+    crate::{diskstore::flock::FileLock, IoResult, PID_FILE_PATH},
+    core::time::Duration,
+    std::{
+        fs,
+        io::ErrorKind,
+        path::Path,
+        process,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    tokio::{sync::broadcast::Receiver, time},
+};
+
+/// The number of seconds between heartbeats
+const HEARTBEAT_INTERVAL: u64 = 5;
+/// The number of seconds after the last heartbeat for which a lease is valid
+const LEASE_TTL: u64 = 3 * HEARTBEAT_INTERVAL;
+
+/// A lease on the data directory
+#[derive(Debug, PartialEq, Eq)]
+struct Lease {
+    pid: u32,
+    /// the time of the last heartbeat (in seconds since the UNIX epoch). Older versions only
+    /// wrote the PID, so this might be missing
+    heartbeat: Option<u64>,
+}
+
+impl Lease {
+    fn new(pid: u32, heartbeat: u64) -> Self {
+        Self {
+            pid,
+            heartbeat: Some(heartbeat),
+        }
+    }
+    /// Parse a lease, returning `None` if the lease was cleared
+    fn parse(src: &str) -> Option<Self> {
+        let mut lines = src.lines();
+        let pid = lines.next()?.trim().parse().ok()?;
+        let heartbeat = lines.next().and_then(|line| line.trim().parse().ok());
+        Some(Self { pid, heartbeat })
+    }
+    fn encode(&self) -> String {
+        match self.heartbeat {
+            Some(heartbeat) => format!("{}\n{heartbeat}\n", self.pid),
+            None => format!("{}\n", self.pid),
+        }
+    }
+    /// Describe when the last heartbeat was
+    fn describe_heartbeat(&self, now: u64) -> String {
+        match self.heartbeat {
+            Some(heartbeat) => {
+                format!("last heartbeat {}s ago", now.saturating_sub(heartbeat))
+            }
+            None => "no heartbeat recorded".to_owned(),
+        }
+    }
+    fn is_expired(&self, now: u64) -> bool {
+        self.heartbeat
+            .map_or(true, |heartbeat| now.saturating_sub(heartbeat) > LEASE_TTL)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Read the lease in the PID file, if any
+fn read_lease() -> IoResult<Option<Lease>> {
+    match fs::read_to_string(PID_FILE_PATH) {
+        Ok(src) => Ok(Lease::parse(&src)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Take the lease on the (locked) PID file, taking over from a holder that didn't shut down
+/// cleanly
+pub fn take(pid_file: &mut FileLock) -> IoResult<()> {
+    if let Some(lease) = self::read_lease()? {
+        log::warn!(
+            "The previous instance (PID {}, {}) didn't shut down cleanly. Taking over the data directory",
+            lease.pid,
+            lease.describe_heartbeat(now_secs())
+        );
+    }
+    self::renew(pid_file)
+}
+
+/// Renew the lease on the (locked) PID file
+fn renew(pid_file: &mut FileLock) -> IoResult<()> {
+    pid_file.write(Lease::new(process::id(), now_secs()).encode().as_bytes())
+}
+
+/// Clear the lease on the (locked) PID file. This must be done before unlocking it
+pub fn release(pid_file: &mut FileLock) -> IoResult<()> {
+    pid_file.write(&[])
+}
+
+/// The heartbeat service
+///
+/// This renews the lease every [`HEARTBEAT_INTERVAL`] seconds until [`dbnet::run`] broadcasts a
+/// termination signal
+pub async fn heartbeat_service(mut pid_file: FileLock, mut termination_signal: Receiver<()>) {
+    loop {
+        tokio::select! {
+            _ = time::sleep(Duration::from_secs(HEARTBEAT_INTERVAL)) => {
+                if let Err(e) = self::renew(&mut pid_file) {
+                    log::error!("Failed to renew the lease on the PID file: {e}");
+                }
+            }
+            _ = termination_signal.recv() => break,
+        }
+    }
+    log::info!("Heartbeat service has exited");
+}
+
+/// Print the state of the lock on the data directory, returning true if the directory can be
+/// used (that is, it isn't held by a live process)
+pub fn lock_status() -> bool {
+    if !Path::new(PID_FILE_PATH).exists() {
+        println!("free: no instance is using the data directory");
+        return true;
+    }
+    let lease = match self::read_lease() {
+        Ok(lease) => lease,
+        Err(e) => {
+            log::error!("Failed to read the PID file: {e}");
+            return false;
+        }
+    };
+    let now = now_secs();
+    // if we can lock the file, nobody is holding it
+    let locked = match FileLock::lock(PID_FILE_PATH) {
+        Ok(mut pid_file) => {
+            if let Err(e) = pid_file.unlock() {
+                log::error!("Failed to unlock the PID file: {e}");
+                return false;
+            }
+            false
+        }
+        Err(_) => true,
+    };
+    match (locked, lease) {
+        (true, Some(lease)) => {
+            let expired = if lease.is_expired(now) {
+                ". The lease has expired, so the process might be hung"
+            } else {
+                ""
+            };
+            println!(
+                "held: PID {} ({}){expired}",
+                lease.pid,
+                lease.describe_heartbeat(now)
+            );
+            false
+        }
+        (true, None) => {
+            // the holder hasn't written its lease yet
+            println!("held: the data directory is locked by another process");
+            false
+        }
+        (false, Some(lease)) => {
+            println!(
+                "stale: PID {} ({}) didn't shut down cleanly. The next start will take over",
+                lease.pid,
+                lease.describe_heartbeat(now)
+            );
+            true
+        }
+        (false, None) => {
+            println!("free: no instance is using the data directory");
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lease, LEASE_TTL};
+
+    #[test]
+    fn lease_encoding() {
+        let lease = Lease::new(42, 1_700_000_000);
+        assert_eq!(Lease::parse(&lease.encode()), Some(lease));
+        // older versions only wrote the PID
+        assert_eq!(
+            Lease::parse("42"),
+            Some(Lease {
+                pid: 42,
+                heartbeat: None
+            })
+        );
+        // a cleared lease
+        assert_eq!(Lease::parse(""), None);
+    }
+
+    #[test]
+    fn lease_expiry() {
+        let lease = Lease::new(42, 100);
+        assert!(!lease.is_expired(100 + LEASE_TTL));
+        assert!(lease.is_expired(100 + LEASE_TTL + 1));
+        assert!(Lease::parse("42").unwrap().is_expired(100));
+    }
+}
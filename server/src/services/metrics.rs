@@ -0,0 +1,275 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Metrics
+//!
+//! An optional HTTP endpoint that exposes internal counters at `/metrics` in the Prometheus
+//! text exposition format. Like the health probe, it doesn't need authentication, so it should
+//! only be reachable from trusted networks. The counters are always maintained (they're just
+//! relaxed atomics), except for the query classification which is only done when the endpoint
+//! is enabled
+
+use {
+    super::health::{self, REQUEST_BUFFER_SIZE, RESP_BAD_REQUEST, RESP_NOT_FOUND},
+    crate::{
+        registry,
+        util::error::{Error, SkyResult},
+    },
+    core::{
+        fmt::Write,
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    std::net::SocketAddr,
+    tokio::{
+        io::AsyncWriteExt,
+        net::{TcpListener, TcpStream},
+        sync::broadcast::Receiver,
+    },
+};
+
+/// The kind of a query (or a stage of a pipeline)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryKind {
+    Read = 0,
+    Write = 1,
+    Admin = 2,
+    BlueQL = 3,
+}
+
+const QUERY_KIND_LABELS: [&str; 4] = ["read", "write", "admin", "blueql"];
+
+/// Whether the metrics endpoint is enabled
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static QUERIES: [AtomicU64; 4] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+static QUERIES_RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
+static QUERIES_TOO_LARGE: AtomicU64 = AtomicU64::new(0);
+static CONNECTIONS_ACCEPTED: AtomicU64 = AtomicU64::new(0);
+static CONNECTIONS_ACTIVE: AtomicU64 = AtomicU64::new(0);
+static TLS_HANDSHAKE_FAILURES: AtomicU64 = AtomicU64::new(0);
+static BGSAVE_OKAY: AtomicU64 = AtomicU64::new(0);
+static BGSAVE_FAILED: AtomicU64 = AtomicU64::new(0);
+
+/// Returns true if the metrics endpoint is enabled
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn record_query(kind: QueryKind) {
+    QUERIES[kind as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_rate_limited() {
+    QUERIES_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_too_large() {
+    QUERIES_TOO_LARGE.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn connection_opened() {
+    CONNECTIONS_ACCEPTED.fetch_add(1, Ordering::Relaxed);
+    CONNECTIONS_ACTIVE.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn connection_closed() {
+    CONNECTIONS_ACTIVE.fetch_sub(1, Ordering::Relaxed);
+}
+
+pub fn record_tls_handshake_failure() {
+    TLS_HANDSHAKE_FAILURES.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_bgsave(okay: bool) {
+    if okay {
+        BGSAVE_OKAY.fetch_add(1, Ordering::Relaxed);
+    } else {
+        BGSAVE_FAILED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Bind the metrics endpoint to the given address
+pub async fn bind(addr: SocketAddr) -> SkyResult<TcpListener> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| Error::ioerror_extra(e, format!("binding metrics endpoint to {addr}")))?;
+    ENABLED.store(true, Ordering::Relaxed);
+    log::info!("Metrics endpoint listening on http://{addr}/metrics");
+    Ok(listener)
+}
+
+/// The metrics service
+///
+/// This keeps serving metrics until [`dbnet::run`] broadcasts a termination signal
+pub async fn metrics_service(listener: TcpListener, mut termination_signal: Receiver<()>) {
+    loop {
+        tokio::select! {
+            ret = listener.accept() => {
+                match ret {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_scrape(stream));
+                    }
+                    Err(e) => log::warn!("Metrics endpoint failed to accept connection: {e}"),
+                }
+            }
+            _ = termination_signal.recv() => break,
+        }
+    }
+    log::info!("Metrics endpoint has exited");
+}
+
+async fn handle_scrape(mut stream: TcpStream) {
+    let mut buf = [0u8; REQUEST_BUFFER_SIZE];
+    let request_line = match health::read_request_line(&mut stream, &mut buf).await {
+        Some(line) => line,
+        None => return,
+    };
+    let _ = match health::get_path(request_line) {
+        Some(b"/metrics") => {
+            let body = render();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            match stream.write_all(head.as_bytes()).await {
+                Ok(()) => stream.write_all(body.as_bytes()).await,
+                Err(e) => Err(e),
+            }
+        }
+        Some(_) => stream.write_all(RESP_NOT_FOUND).await,
+        None => stream.write_all(RESP_BAD_REQUEST).await,
+    };
+    let _ = stream.shutdown().await;
+}
+
+/// Write a metric with the given samples (each with an optional label)
+fn write_metric(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    kind: &str,
+    samples: &[(Option<(&str, &str)>, u64)],
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    for (label, value) in samples {
+        let _ = match label {
+            Some((key, val)) => writeln!(out, "{name}{{{key}=\"{val}\"}} {value}"),
+            None => writeln!(out, "{name} {value}"),
+        };
+    }
+}
+
+/// Render all the metrics in the Prometheus text exposition format
+fn render() -> String {
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+    let mut out = String::new();
+    let queries: Vec<_> = QUERY_KIND_LABELS
+        .iter()
+        .zip(QUERIES.iter())
+        .map(|(label, counter)| (Some(("kind", *label)), load(counter)))
+        .collect();
+    write_metric(
+        &mut out,
+        "skyd_queries_total",
+        "The number of queries run (pipelines count every stage)",
+        "counter",
+        &queries,
+    );
+    write_metric(
+        &mut out,
+        "skyd_queries_rejected_total",
+        "The number of queries rejected because of limits",
+        "counter",
+        &[
+            (
+                Some(("reason", "rate-limited")),
+                load(&QUERIES_RATE_LIMITED),
+            ),
+            (
+                Some(("reason", "query-too-large")),
+                load(&QUERIES_TOO_LARGE),
+            ),
+        ],
+    );
+    write_metric(
+        &mut out,
+        "skyd_connections_accepted_total",
+        "The number of connections accepted",
+        "counter",
+        &[(None, load(&CONNECTIONS_ACCEPTED))],
+    );
+    write_metric(
+        &mut out,
+        "skyd_connections_active",
+        "The number of open connections",
+        "gauge",
+        &[(None, load(&CONNECTIONS_ACTIVE))],
+    );
+    write_metric(
+        &mut out,
+        "skyd_tls_handshake_failures_total",
+        "The number of failed TLS handshakes",
+        "counter",
+        &[(None, load(&TLS_HANDSHAKE_FAILURES))],
+    );
+    write_metric(
+        &mut out,
+        "skyd_bgsave_total",
+        "The number of BGSAVE runs, by outcome",
+        "counter",
+        &[
+            (Some(("result", "okay")), load(&BGSAVE_OKAY)),
+            (Some(("result", "failed")), load(&BGSAVE_FAILED)),
+        ],
+    );
+    write_metric(
+        &mut out,
+        "skyd_healthy",
+        "Whether disk writes are succeeding (1) or the server is poisoned (0)",
+        "gauge",
+        &[(None, registry::health_okay() as u64)],
+    );
+    out
+}
+
+#[test]
+fn test_render() {
+    let metrics = render();
+    assert!(metrics.contains("# TYPE skyd_queries_total counter\n"));
+    assert!(metrics.contains("skyd_queries_total{kind=\"blueql\"} "));
+    assert!(metrics.contains("skyd_queries_rejected_total{reason=\"rate-limited\"} "));
+    assert!(metrics.contains("# TYPE skyd_connections_active gauge\n"));
+    // every sample is on its own line and has a value
+    assert!(metrics
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .all(|line| line.rsplit(' ').next().unwrap().parse::<u64>().is_ok()));
+}
@@ -25,21 +25,131 @@
 */
 
 pub mod bgsave;
+pub mod export;
+pub mod health;
+pub mod history;
+pub mod import;
+pub mod lease;
+pub mod metrics;
+pub mod seed;
+pub mod selftest;
 pub mod snapshot;
-use crate::{
-    corestore::memstore::Memstore, diskstore::flock::FileLock, storage, util::os, IoResult,
+use {
+    crate::{
+        config::{StorageSettings, Task},
+        corestore::memstore::Memstore,
+        diskstore::flock::FileLock,
+        storage::{
+            self,
+            v1::{
+                interface::{DIR_BACKUPS, DIR_KSROOT, DIR_RSNAPROOT, DIR_SNAPROOT},
+                manifest::{BackupManifest, MANIFEST_FILE},
+            },
+        },
+        util::os,
+        IoResult,
+    },
+    std::{
+        fs,
+        io::{Error as IoError, ErrorKind},
+        path::Path,
+    },
 };
 
 pub fn restore_data(src: Option<String>) -> IoResult<()> {
     if let Some(src) = src {
-        // hmm, so restore it
-        os::recursive_copy(src, "data")?;
+        // check the backup before we touch anything. older backups don't have a manifest, so
+        // there's nothing that we can check for them
+        match BackupManifest::read(&src) {
+            Ok(manifest) => {
+                let problems = manifest.validate(&src);
+                if !problems.is_empty() {
+                    problems
+                        .iter()
+                        .for_each(|problem| log::error!("{}", problem));
+                    return Err(IoError::new(
+                        ErrorKind::InvalidData,
+                        "refusing to restore from an incomplete snapshot",
+                    ));
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        // hmm, so restore it. snapshots are laid out just like the keyspace root
+        os::recursive_copy(src, DIR_KSROOT)?;
+        // we might be restoring into an empty directory, so create the rest of the tree as well
+        for dir in [DIR_SNAPROOT, DIR_RSNAPROOT, DIR_BACKUPS] {
+            fs::create_dir_all(dir)?;
+        }
+        // the manifest describes the backup and isn't a part of the data
+        match fs::remove_file(Path::new(DIR_KSROOT).join(MANIFEST_FILE)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
         log::info!("Successfully restored data from snapshot");
     }
     Ok(())
 }
 
+/// Run a task instead of starting the server. Returns true if the task succeeded
+pub fn run_task(task: &Task, settings: &StorageSettings) -> bool {
+    match task {
+        Task::BackupInfo(dir) => self::describe_backup(dir),
+        Task::Selftest => selftest::run(),
+        Task::Import(import) => import::run(import, settings),
+        Task::Export(export) => export::run(export, settings),
+        Task::LockStatus => lease::lock_status(),
+    }
+}
+
+/// Print the manifest of the backup in `dir` and check that the backup is complete, returning
+/// true if it is
+pub fn describe_backup(dir: &str) -> bool {
+    let manifest = match BackupManifest::read(dir) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            log::error!(
+                "Failed to read the manifest of the backup in `{}`: {}",
+                dir,
+                e
+            );
+            return false;
+        }
+    };
+    println!(
+        "Created: {} (Skytable v{})",
+        manifest.created, manifest.version
+    );
+    println!("Spaces: {}", manifest.spaces.join(", "));
+    println!("Users: {}", manifest.users.join(", "));
+    println!("Models:");
+    for model in manifest.models.iter() {
+        let storage = if model.volatile {
+            "volatile"
+        } else {
+            "persistent"
+        };
+        println!(
+            "  {}.{} {}, {}, {} rows",
+            model.space, model.model, model.declaration, storage, model.rows
+        );
+    }
+    let problems = manifest.validate(dir);
+    problems
+        .iter()
+        .for_each(|problem| log::error!("{}", problem));
+    problems.is_empty()
+}
+
 pub fn pre_shutdown_cleanup(mut pid_file: FileLock, mr: Option<&Memstore>) -> bool {
+    if let Err(e) = lease::release(&mut pid_file) {
+        log::error!(
+            "Shutdown failure: Failed to release the lease on the pid file: {}",
+            e
+        );
+        return false;
+    }
     if let Err(e) = pid_file.unlock() {
         log::error!("Shutdown failure: Failed to unlock pid file: {}", e);
         return false;
@@ -0,0 +1,103 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Seed scripts
+//!
+//! A directory of BlueQL seed scripts can be passed with `--init-dir` to bootstrap a new
+//! instance (create spaces and models) without any external tooling. The scripts (files with the
+//! `.bql` extension) are run in the order of their names, and only when the instance is first
+//! initialized: an instance with existing data (or one that was restored from a backup) is never
+//! seeded. Every line of a script is a statement, and empty lines or lines starting with `#` are
+//! skipped. Each script starts out in `default.default`, so `use` only applies to the rest of the
+//! script.
+//!
+//! If a script fails, the server refuses to start. The data directory has already been created by
+//! then, so it has to be removed before the scripts can be retried
+
+use {
+    crate::{
+        blueql,
+        corestore::Corestore,
+        util::error::{Error, SkyResult},
+        IoResult,
+    },
+    std::{fs, path::PathBuf},
+};
+
+/// The extension for seed scripts
+const SEED_EXTENSION: &str = "bql";
+
+/// Run all the seed scripts in `dir` against the store
+pub fn run_seeds(db: &Corestore, dir: &str) -> SkyResult<()> {
+    let scripts = self::list_scripts(dir)
+        .map_err(|e| Error::ioerror_extra(e, format!("reading the seed directory `{dir}`")))?;
+    for script in scripts {
+        let src = fs::read_to_string(&script).map_err(|e| {
+            Error::ioerror_extra(e, format!("reading the seed script `{}`", script.display()))
+        })?;
+        let mut handle = db.clone();
+        for (lineno, statement) in self::statements(&src) {
+            blueql::execute_seed(&mut handle, statement.as_bytes()).map_err(|e| {
+                Error::OtherError(format!(
+                    "seed script `{}` failed on line {lineno}: {e}",
+                    script.display()
+                ))
+            })?;
+        }
+        log::info!("Applied seed script `{}`", script.display());
+    }
+    Ok(())
+}
+
+/// Returns the paths to the seed scripts in `dir`, sorted by name
+fn list_scripts(dir: &str) -> IoResult<Vec<PathBuf>> {
+    let mut scripts = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().map_or(false, |ext| ext == SEED_EXTENSION) {
+            scripts.push(path);
+        }
+    }
+    scripts.sort();
+    Ok(scripts)
+}
+
+/// Returns the statements in a seed script along with their line numbers
+fn statements(src: &str) -> impl Iterator<Item = (usize, &str)> {
+    src.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+#[test]
+fn seed_statements() {
+    let src = "# bootstrap\ncreate space app\n\n  use app  \n#create model x(string, string)\n";
+    assert_eq!(
+        statements(src).collect::<Vec<_>>(),
+        [(2, "create space app"), (4, "use app")]
+    );
+}
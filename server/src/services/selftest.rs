@@ -0,0 +1,292 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Self-test
+//!
+//! `skyd --selftest` exercises the storage paths end-to-end in a temporary data directory
+//! (creating spaces and models, writing rows, flushing and reloading them, snapshotting,
+//! verifying the snapshot and restoring from it) and prints a pass/fail report. This is a quick
+//! way to check that a build works on a platform (filesystem semantics, permissions and such)
+//! before trusting it with any data
+
+use {
+    crate::{
+        config::StorageSettings,
+        corestore::{
+            memstore::{Memstore, ObjectID},
+            table::{DataModel, Table},
+            SharedSlice,
+        },
+        registry,
+        storage::v1::{
+            diff,
+            flush::{self, Autoflush, LocalSnapshot},
+            interface::{DIR_KSROOT, DIR_SNAPROOT},
+            manifest::BackupManifest,
+            unflush,
+        },
+    },
+    std::{env, fs, path::Path, process},
+};
+
+type CheckResult = Result<(), String>;
+type Check = fn(&mut Option<Memstore>) -> CheckResult;
+
+/// The number of rows written into the test model
+const ROWS: usize = 1000;
+/// The name of the snapshot that we create
+const SNAPSHOT: &str = "selftest";
+
+const CHECKS: [(&str, Check); 7] = [
+    ("create a data directory", self::create_data_directory),
+    ("create a space and models", self::create_space_and_models),
+    ("write, update and delete rows", self::write_rows),
+    ("flush and reload the data", self::flush_and_reload),
+    ("create a snapshot", self::create_snapshot),
+    ("verify the snapshot", self::verify_snapshot),
+    ("restore from the snapshot", self::restore_snapshot),
+];
+
+fn space() -> ObjectID {
+    unsafe {
+        // UNSAFE(@ohsayan): the name is well within the limits of an object ID
+        ObjectID::from_slice("selftest")
+    }
+}
+
+fn model() -> ObjectID {
+    unsafe {
+        // UNSAFE(@ohsayan): the name is well within the limits of an object ID
+        ObjectID::from_slice("strings")
+    }
+}
+
+fn volatile_model() -> ObjectID {
+    unsafe {
+        // UNSAFE(@ohsayan): the name is well within the limits of an object ID
+        ObjectID::from_slice("cache")
+    }
+}
+
+fn ensure(cond: bool, msg: impl FnOnce() -> String) -> CheckResult {
+    if cond {
+        Ok(())
+    } else {
+        Err(msg())
+    }
+}
+
+fn store(store: &Option<Memstore>) -> Result<&Memstore, String> {
+    store
+        .as_ref()
+        .ok_or_else(|| "no data was loaded".to_owned())
+}
+
+fn table(store: &Memstore, tblid: &ObjectID) -> Result<std::sync::Arc<Table>, String> {
+    store
+        .get_keyspace_atomic_ref(&self::space())
+        .and_then(|ks| ks.get_table_atomic_ref(tblid))
+        .ok_or_else(|| "the test model is missing".to_owned())
+}
+
+/// Check that the test model has exactly the rows that [`write_rows`] left behind
+fn check_rows(store: &Memstore) -> CheckResult {
+    let table = self::table(store, &self::model())?;
+    ensure(table.model_declaration() == "(string, string)", || {
+        format!("the test model is now `{}`", table.model_declaration())
+    })?;
+    let kve = match table.get_model_ref() {
+        DataModel::KV(kve) => kve,
+        _ => return Err("the test model has the wrong data model".to_owned()),
+    };
+    ensure(kve.len() == ROWS / 2, || {
+        format!("expected {} rows but found {}", ROWS / 2, kve.len())
+    })?;
+    for i in (0..ROWS).step_by(2) {
+        let value = kve
+            .get_cloned(format!("key-{i}"))
+            .map_err(|_| "a row has a bad encoding".to_owned())?;
+        let expected = format!("updated-{i}");
+        ensure(value.as_deref() == Some(expected.as_bytes()), || {
+            format!("row `key-{i}` has the wrong value")
+        })?;
+    }
+    Ok(())
+}
+
+fn create_data_directory(store: &mut Option<Memstore>) -> CheckResult {
+    let loaded = unflush::read_full(&StorageSettings::default()).map_err(|e| e.to_string())?;
+    ensure(Path::new(DIR_KSROOT).join("PRELOAD").is_file(), || {
+        "the data directory wasn't initialized".to_owned()
+    })?;
+    *store = Some(loaded);
+    Ok(())
+}
+
+fn create_space_and_models(store: &mut Option<Memstore>) -> CheckResult {
+    let store = self::store(store)?;
+    ensure(store.create_keyspace(self::space()), || {
+        "failed to create the test space".to_owned()
+    })?;
+    let ks = store
+        .get_keyspace_atomic_ref(&self::space())
+        .ok_or_else(|| "the test space is missing".to_owned())?;
+    let created = ks.create_table(self::model(), Table::from_model_code(2, false).unwrap())
+        && ks.create_table(
+            self::volatile_model(),
+            Table::from_model_code(7, true).unwrap(),
+        );
+    ensure(created, || "failed to create the test models".to_owned())?;
+    // the tree needs to be re-initialized on the next flush (just like with `create`)
+    registry::get_preload_tripswitch().trip();
+    Ok(())
+}
+
+fn write_rows(store: &mut Option<Memstore>) -> CheckResult {
+    let store = self::store(store)?;
+    let table = self::table(store, &self::model())?;
+    let kve = match table.get_model_ref() {
+        DataModel::KV(kve) => kve,
+        _ => return Err("the test model has the wrong data model".to_owned()),
+    };
+    let bad_encoding = |_| "a row was refused because of its encoding".to_owned();
+    for i in 0..ROWS {
+        let key = SharedSlice::from(format!("key-{i}"));
+        ensure(
+            kve.set(key, format!("value-{i}").into())
+                .map_err(bad_encoding)?,
+            || format!("row `key-{i}` already exists"),
+        )?;
+    }
+    for i in 0..ROWS {
+        let key = SharedSlice::from(format!("key-{i}"));
+        let updated = if i % 2 == 0 {
+            kve.update(key, format!("updated-{i}").into())
+        } else {
+            kve.remove(key)
+        };
+        ensure(updated.map_err(bad_encoding)?, || {
+            format!("row `key-{i}` is missing")
+        })?;
+    }
+    // a bad encoding must be refused
+    ensure(kve.set("bad".into(), vec![0xFF].into()).is_err(), || {
+        "a value with a bad encoding was accepted".to_owned()
+    })?;
+    self::check_rows(store)
+}
+
+fn flush_and_reload(store: &mut Option<Memstore>) -> CheckResult {
+    flush::flush_full(Autoflush, self::store(store)?).map_err(|e| e.to_string())?;
+    // drop the old data before we load it back
+    *store = None;
+    let loaded = unflush::read_full(&StorageSettings::default()).map_err(|e| e.to_string())?;
+    self::check_rows(&loaded)?;
+    let cache = self::table(&loaded, &self::volatile_model())?;
+    ensure(cache.is_volatile(), || {
+        "the volatile model is no longer volatile".to_owned()
+    })?;
+    *store = Some(loaded);
+    Ok(())
+}
+
+fn create_snapshot(store: &mut Option<Memstore>) -> CheckResult {
+    let store = self::store(store)?;
+    flush::flush_full(LocalSnapshot::new(SNAPSHOT.to_owned()), store).map_err(|e| e.to_string())?;
+    let snapshot = Path::new(DIR_SNAPROOT).join(SNAPSHOT);
+    let manifest = BackupManifest::read(&snapshot).map_err(|e| e.to_string())?;
+    ensure(manifest.models.iter().any(|m| m.model == "strings"), || {
+        "the manifest doesn't have the test model".to_owned()
+    })?;
+    match manifest.validate(&snapshot).pop() {
+        Some(problem) => Err(problem),
+        None => Ok(()),
+    }
+}
+
+fn verify_snapshot(store: &mut Option<Memstore>) -> CheckResult {
+    let snapshot = Path::new(DIR_SNAPROOT).join(SNAPSHOT);
+    let drift = diff::diff_backup(&snapshot, self::store(store)?).map_err(|e| e.to_string())?;
+    match drift.into_iter().next() {
+        Some(drift) => Err(drift),
+        None => Ok(()),
+    }
+}
+
+fn restore_snapshot(store: &mut Option<Memstore>) -> CheckResult {
+    *store = None;
+    fs::remove_dir_all(DIR_KSROOT).map_err(|e| e.to_string())?;
+    let snapshot = Path::new(DIR_SNAPROOT).join(SNAPSHOT);
+    super::restore_data(Some(snapshot.to_string_lossy().into_owned()))
+        .map_err(|e| e.to_string())?;
+    let loaded = unflush::read_full(&StorageSettings::default()).map_err(|e| e.to_string())?;
+    self::check_rows(&loaded)?;
+    *store = Some(loaded);
+    Ok(())
+}
+
+/// Run the self-test, printing a report. Returns true if every check passed
+pub fn run() -> bool {
+    let root = env::temp_dir().join(format!("skyd-selftest-{}", process::id()));
+    let cwd = match env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(e) => {
+            log::error!("Failed to get the current directory: {e}");
+            return false;
+        }
+    };
+    println!("Running the self-test in {}", root.display());
+    if let Err(e) = fs::create_dir_all(&root).and_then(|_| env::set_current_dir(&root)) {
+        log::error!("Failed to create a temporary data directory: {e}");
+        return false;
+    }
+    let mut store = None;
+    let mut passed = 0;
+    let mut failed = false;
+    for (name, check) in CHECKS {
+        if failed {
+            // every check depends on the ones before it
+            println!("SKIP  {name}");
+            continue;
+        }
+        match check(&mut store) {
+            Ok(()) => {
+                passed += 1;
+                println!("PASS  {name}");
+            }
+            Err(e) => {
+                failed = true;
+                println!("FAIL  {name}: {e}");
+            }
+        }
+    }
+    drop(store);
+    if let Err(e) = env::set_current_dir(cwd).and_then(|_| fs::remove_dir_all(&root)) {
+        log::warn!("Failed to remove the temporary data directory: {e}");
+    }
+    println!("{passed} of {} checks passed", CHECKS.len());
+    !failed
+}
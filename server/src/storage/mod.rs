@@ -68,8 +68,11 @@ in practice).
 pub mod v1;
 
 pub mod unflush {
-    use crate::{corestore::memstore::Memstore, storage::v1::error::StorageEngineResult};
-    pub fn read_full() -> StorageEngineResult<Memstore> {
-        super::v1::unflush::read_full()
+    use crate::{
+        config::StorageSettings, corestore::memstore::Memstore,
+        storage::v1::error::StorageEngineResult,
+    };
+    pub fn read_full(settings: &StorageSettings) -> StorageEngineResult<Memstore> {
+        super::v1::unflush::read_full(settings)
     }
 }
@@ -0,0 +1,76 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+extern crate test;
+use {
+    super::{flush, unflush},
+    crate::corestore::{
+        memstore::{Keyspace, ObjectID},
+        table::Table,
+    },
+    std::fs,
+    test::Bencher,
+};
+
+const TABLE_COUNT: usize = 16;
+const ROWS_PER_TABLE: usize = 10_000;
+
+/// Create a keyspace with a few large tables on disk
+fn setup_keyspace(ksname: &str) -> ObjectID {
+    fs::create_dir_all(format!("data/ks/{ksname}")).unwrap();
+    let ksid = unsafe { ObjectID::from_slice(ksname) };
+    let ks = Keyspace::empty();
+    for tblno in 0..TABLE_COUNT {
+        let tbl = Table::new_default_kve();
+        let kve = tbl.get_kvstore().unwrap();
+        for row in 0..ROWS_PER_TABLE {
+            kve.set(format!("key{row}").into(), format!("value{row}").into())
+                .unwrap();
+        }
+        let tblid = unsafe { ObjectID::from_slice(&format!("tbl{tblno}")) };
+        assert!(ks.create_table(tblid, tbl));
+    }
+    flush::flush_keyspace_full(&flush::Autoflush, &ksid, &ks).unwrap();
+    ksid
+}
+
+fn bench_restore(b: &mut Bencher, ksname: &str, readahead: usize) {
+    let ksid = setup_keyspace(ksname);
+    b.iter(|| {
+        let ks = unflush::read_keyspace::<Keyspace>(&ksid, readahead).unwrap();
+        assert_eq!(ks.table_count(), TABLE_COUNT);
+    });
+}
+
+#[bench]
+fn restore_keyspace_no_readahead(b: &mut Bencher) {
+    bench_restore(b, "bench_readahead_0", 0)
+}
+
+#[bench]
+fn restore_keyspace_readahead(b: &mut Bencher) {
+    bench_restore(b, "bench_readahead_4", 4)
+}
@@ -0,0 +1,159 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Backup verification
+//!
+//! Routines to compare a backup (anything laid out like `data/ks`, such as a snapshot)
+//! against the live data. For every model we compare the definition, the number of rows and
+//! a sample of the rows themselves, and report any drift that we find
+
+use {
+    super::{
+        bytemarks,
+        error::StorageEngineResult,
+        readahead::Readahead,
+        unflush::{self, UnflushableTable},
+        Coremap,
+    },
+    crate::corestore::{
+        memstore::{Memstore, SYSTEM},
+        table::{DataModel, Table},
+        SharedSlice,
+    },
+    std::path::Path,
+};
+
+/// The maximum number of rows that are compared per model
+const SAMPLE_SIZE: usize = 1000;
+
+/// Compare the backup at `root` with the live data, returning a description of all the drift
+/// that was found (an empty list means that the backup matches)
+pub fn diff_backup(root: &Path, store: &Memstore) -> StorageEngineResult<Vec<String>> {
+    let mut drift = Vec::new();
+    let backup_keyspaces = unflush::read_preload_in(root)?;
+    for ksid in store.list_keyspaces() {
+        if !backup_keyspaces.contains(&ksid) {
+            drift.push(format!("space `{}` is missing in the backup", unsafe {
+                ksid.as_str()
+            }));
+        }
+    }
+    for ksid in backup_keyspaces {
+        if ksid == SYSTEM {
+            continue;
+        }
+        let ksname = unsafe { ksid.as_str() };
+        let live_ks = match store.get_keyspace_atomic_ref(&ksid) {
+            Some(ks) => ks,
+            None => {
+                drift.push(format!("space `{ksname}` is missing in the live instance"));
+                continue;
+            }
+        };
+        let partmap = unflush::read_partmap_in(root, &ksid)?;
+        for tblid in live_ks.tables.iter() {
+            if !partmap.contains_key(tblid.key()) {
+                drift.push(format!("model `{ksname}.{}` is missing in the backup", unsafe {
+                    tblid.key().as_str()
+                }));
+            }
+        }
+        for (tblid, (table_storage_type, model_code)) in partmap {
+            let entity = format!("{ksname}.{}", unsafe { tblid.as_str() });
+            let live_tbl = match live_ks.get_table_atomic_ref(&tblid) {
+                Some(tbl) => tbl,
+                None => {
+                    drift.push(format!("model `{entity}` is missing in the live instance"));
+                    continue;
+                }
+            };
+            let volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
+            if live_tbl.get_model_code() != model_code || live_tbl.is_volatile() != volatile {
+                drift.push(format!("model `{entity}` has a different definition"));
+                continue;
+            }
+            if volatile || live_tbl.is_unavailable() {
+                // there's nothing on disk for volatile models and we don't have the live data
+                // for unavailable models
+                continue;
+            }
+            let filepath = root.join(ksname).join(unsafe { tblid.as_str() });
+            let backup_tbl = Table::unflush_table(
+                filepath,
+                model_code,
+                volatile,
+                &mut Readahead::disabled(),
+            )?;
+            let (backup_rows, live_rows) = (backup_tbl.count(), live_tbl.count());
+            if backup_rows != live_rows {
+                drift.push(format!(
+                    "model `{entity}` has {backup_rows} rows in the backup and {live_rows} rows in the live instance"
+                ));
+            }
+            let (sampled, mismatched) = self::sample_table(&backup_tbl, &live_tbl);
+            if mismatched != 0 {
+                drift.push(format!(
+                    "model `{entity}` has {mismatched} of {sampled} sampled rows that differ"
+                ));
+            }
+        }
+    }
+    Ok(drift)
+}
+
+/// Compare a sample of the rows in the backup with the live rows, returning the number of rows
+/// sampled and the number of rows that were either missing or different in the live model
+fn sample_table(backup: &Table, live: &Table) -> (usize, usize) {
+    match (backup.get_model_ref(), live.get_model_ref()) {
+        (DataModel::KV(backup), DataModel::KV(live)) => {
+            self::sample_rows(backup.get_inner_ref(), live.get_inner_ref(), |a, b| a == b)
+        }
+        (DataModel::KVExtListmap(backup), DataModel::KVExtListmap(live)) => {
+            self::sample_rows(backup.get_inner_ref(), live.get_inner_ref(), |a, b| {
+                *a.read() == *b.read()
+            })
+        }
+        _ => (0, 0),
+    }
+}
+
+fn sample_rows<T>(
+    backup: &Coremap<SharedSlice, T>,
+    live: &Coremap<SharedSlice, T>,
+    eq: impl Fn(&T, &T) -> bool,
+) -> (usize, usize) {
+    let step = (backup.len() / SAMPLE_SIZE).max(1);
+    let mut sampled = 0;
+    let mut mismatched = 0;
+    for row in backup.iter().step_by(step).take(SAMPLE_SIZE) {
+        sampled += 1;
+        match live.get(row.key()) {
+            Some(value) if eq(row.value(), &value) => {}
+            _ => mismatched += 1,
+        }
+    }
+    (sampled, mismatched)
+}
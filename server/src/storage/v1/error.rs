@@ -49,6 +49,8 @@ pub enum StorageEngineError {
     CorruptedFile(String),
     /// The file contains bad metadata
     BadMetadata(String),
+    /// The data is estimated to need more memory (in MiB) than what is permitted
+    OverMemoryLimit { estimate: u64, memlimit: u64 },
 }
 
 impl StorageEngineError {
@@ -85,6 +87,10 @@ impl fmt::Display for StorageEngineError {
             Self::IoErrorExtra(ioe, extra) => write!(f, "I/O error while {extra}: {ioe}"),
             Self::CorruptedFile(cfile) => write!(f, "file `{cfile}` is corrupted"),
             Self::BadMetadata(file) => write!(f, "bad metadata in file `{file}`"),
+            Self::OverMemoryLimit { estimate, memlimit } => write!(
+                f,
+                "data is estimated to need {estimate} MiB of memory which is over the limit of {memlimit} MiB. Use a forced load to load it anyway"
+            ),
         }
     }
 }
@@ -54,6 +54,8 @@ pub trait StorageTarget {
     ///
     /// Example cases where this doesn't apply: snapshots
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool;
+    /// This storage target is a backup and should carry a manifest of the schema
+    const NEEDS_MANIFEST: bool;
     /// The root for this storage target. **Must not be separator terminated!**
     fn root(&self) -> String;
     /// Returns the path to the `PRELOAD_` **temporary file** ($ROOT/PRELOAD)
@@ -94,6 +96,7 @@ pub struct Autoflush;
 impl StorageTarget for Autoflush {
     const NEEDS_TREE_INIT: bool = false;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = true;
+    const NEEDS_MANIFEST: bool = false;
     fn root(&self) -> String {
         String::from(interface::DIR_KSROOT)
     }
@@ -113,6 +116,7 @@ impl<'a> RemoteSnapshot<'a> {
 impl<'a> StorageTarget for RemoteSnapshot<'a> {
     const NEEDS_TREE_INIT: bool = true;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = false;
+    const NEEDS_MANIFEST: bool = true;
     fn root(&self) -> String {
         let mut p = String::from(interface::DIR_RSNAPROOT);
         p.push('/');
@@ -135,6 +139,7 @@ impl LocalSnapshot {
 impl StorageTarget for LocalSnapshot {
     const NEEDS_TREE_INIT: bool = true;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = false;
+    const NEEDS_MANIFEST: bool = true;
     fn root(&self) -> String {
         let mut p = String::from(interface::DIR_SNAPROOT);
         p.push('/');
@@ -179,6 +184,10 @@ pub trait FlushableTable {
     fn write_table_to<W: Write>(&self, writer: &mut W) -> IoResult<()>;
     /// Returns the model code bytemark
     fn model_code(&self) -> u8;
+    /// The table's data was never loaded, so it's only on disk
+    fn is_unavailable(&self) -> bool {
+        false
+    }
 }
 
 impl FlushableTable for Table {
@@ -191,6 +200,8 @@ impl FlushableTable for Table {
             DataModel::KVExtListmap(ref kvl) => {
                 super::se::raw_serialize_list_map(kvl.get_inner_ref(), writer)
             }
+            // flush_table never serializes these; their files are carried over as-is
+            DataModel::Unavailable(_) => unsafe { impossible!() },
         }
     }
     fn storage_code(&self) -> u8 {
@@ -199,6 +210,9 @@ impl FlushableTable for Table {
     fn model_code(&self) -> u8 {
         self.get_model_code()
     }
+    fn is_unavailable(&self) -> bool {
+        self.is_unavailable()
+    }
 }
 
 impl FlushableTable for SystemTable {
@@ -245,6 +259,9 @@ pub fn flush_full<T: StorageTarget>(target: T, store: &Memstore) -> IoResult<()>
     // dummy one because it is located in a different field. So, we need to flush the actual
     // tables
     self::flush_keyspace_full(&target, &SYSTEM, &store.system)?;
+    if T::NEEDS_MANIFEST {
+        super::manifest::BackupManifest::new(store).write(target.root())?;
+    }
     Ok(())
 }
 
@@ -290,6 +307,20 @@ pub mod oneshot {
         if table.is_volatile() {
             // no flushing needed
             Ok(())
+        } else if table.is_unavailable() {
+            // the data was never loaded, so the file on disk is all we have. copy it over as-is
+            // (unless this is the data directory itself)
+            let path = unsafe { target.table_target(ksid.as_str(), tableid.as_str()) };
+            let src = unsafe {
+                concat_str!(interface::DIR_KSROOT, "/", ksid.as_str(), "/", tableid.as_str())
+            };
+            if path[..path.len() - 1] == src {
+                Ok(())
+            } else {
+                cowfile(&path, |file| {
+                    std::io::copy(&mut File::open(&src)?, file).map(|_| ())
+                })
+            }
         } else {
             let path = unsafe { target.table_target(ksid.as_str(), tableid.as_str()) };
             cowfile(&path, |file| {
@@ -47,6 +47,7 @@ pub const DIR_KSROOT: &str = "data/ks";
 pub const DIR_SNAPROOT: &str = "data/snaps";
 pub const DIR_RSNAPROOT: &str = "data/rsnap";
 pub const DIR_BACKUPS: &str = "data/backups";
+pub const DIR_CAPTURES: &str = "data/captures";
 pub const DIR_ROOT: &str = "data";
 
 /// Creates the directories for the keyspaces
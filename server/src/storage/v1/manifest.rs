@@ -0,0 +1,189 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Backup manifests
+//!
+//! Every snapshot carries a `MANIFEST` file with a logical dump of the schema (the spaces,
+//! the models and the users; never the keys) at the time the snapshot was made. This lets
+//! operators see what a backup contains without restoring it and lets us catch incomplete
+//! backups before we restore them
+
+use {
+    crate::{
+        corestore::{
+            memstore::{Memstore, AUTH, SYSTEM},
+            table::SystemDataModel,
+        },
+        IoResult,
+    },
+    chrono::prelude::Utc,
+    libsky::VERSION,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        io::{Error as IoError, ErrorKind},
+        path::Path,
+    },
+};
+
+/// The name of the manifest file in the backup's root
+pub const MANIFEST_FILE: &str = "MANIFEST";
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The logical schema of a backup
+pub struct BackupManifest {
+    /// the version of the server that created the backup
+    pub version: String,
+    /// when the backup was created (RFC 3339)
+    pub created: String,
+    /// the spaces in the backup
+    pub spaces: Vec<String>,
+    /// the users in the backup (just the usernames)
+    pub users: Vec<String>,
+    /// the models in the backup
+    pub models: Vec<ModelManifest>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The definition of a model in a backup
+pub struct ModelManifest {
+    pub space: String,
+    pub model: String,
+    /// the type list, exactly as it would be used in a `create model` statement
+    pub declaration: String,
+    pub volatile: bool,
+    /// the number of rows in the model when the backup was made
+    pub rows: u64,
+}
+
+impl BackupManifest {
+    /// Take the manifest of the current schema
+    pub fn new(store: &Memstore) -> Self {
+        let mut spaces = Vec::new();
+        let mut models = Vec::new();
+        for ks in store.keyspaces.iter() {
+            if *ks.key() == SYSTEM {
+                continue;
+            }
+            let space = unsafe { ks.key().as_str() }.to_owned();
+            for tbl in ks.value().tables.iter() {
+                models.push(ModelManifest {
+                    space: space.clone(),
+                    model: unsafe { tbl.key().as_str() }.to_owned(),
+                    declaration: tbl.value().model_declaration().to_owned(),
+                    volatile: tbl.value().is_volatile(),
+                    rows: tbl.value().count() as u64,
+                });
+            }
+            spaces.push(space);
+        }
+        spaces.sort();
+        models.sort_by(|a, b| (&a.space, &a.model).cmp(&(&b.space, &b.model)));
+        let mut users: Vec<String> = match store.system.tables.get(&AUTH) {
+            Some(tbl) => match tbl.data {
+                SystemDataModel::Auth(ref authmap) => authmap
+                    .iter()
+                    .map(|kv| String::from_utf8_lossy(kv.key()).to_string())
+                    .collect(),
+            },
+            None => Vec::new(),
+        };
+        users.sort();
+        Self {
+            version: VERSION.to_owned(),
+            created: Utc::now().to_rfc3339(),
+            spaces,
+            users,
+            models,
+        }
+    }
+    /// Write the manifest into the backup at `root`
+    pub fn write(&self, root: impl AsRef<Path>) -> IoResult<()> {
+        let manifest =
+            toml::to_string(self).map_err(|e| IoError::new(ErrorKind::InvalidData, e))?;
+        fs::write(root.as_ref().join(MANIFEST_FILE), manifest)
+    }
+    /// Read the manifest of the backup at `root`
+    pub fn read(root: impl AsRef<Path>) -> IoResult<Self> {
+        let manifest = fs::read(root.as_ref().join(MANIFEST_FILE))?;
+        toml::from_slice(&manifest).map_err(|e| IoError::new(ErrorKind::InvalidData, e))
+    }
+    /// Check that the backup at `root` has the data files for every persistent model in the
+    /// manifest, returning a description of every problem that was found
+    pub fn validate(&self, root: impl AsRef<Path>) -> Vec<String> {
+        let root = root.as_ref();
+        self.models
+            .iter()
+            .filter(|model| !model.volatile)
+            .filter(|model| !root.join(&model.space).join(&model.model).is_file())
+            .map(|model| {
+                format!(
+                    "the data file for model `{}.{}` is missing",
+                    model.space, model.model
+                )
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn manifest_roundtrip() {
+    let manifest = BackupManifest {
+        version: VERSION.to_owned(),
+        created: Utc::now().to_rfc3339(),
+        spaces: vec!["default".to_owned(), "twitter".to_owned()],
+        users: vec!["root".to_owned()],
+        models: vec![
+            ModelManifest {
+                space: "default".to_owned(),
+                model: "default".to_owned(),
+                declaration: "(binary, binary)".to_owned(),
+                volatile: false,
+                rows: 10,
+            },
+            ModelManifest {
+                space: "twitter".to_owned(),
+                model: "cache".to_owned(),
+                declaration: "(string, list<string>)".to_owned(),
+                volatile: true,
+                rows: 0,
+            },
+        ],
+    };
+    let dir = std::env::temp_dir().join("skyd-manifest-roundtrip");
+    fs::create_dir_all(&dir).unwrap();
+    manifest.write(&dir).unwrap();
+    assert_eq!(BackupManifest::read(&dir).unwrap(), manifest);
+    // the volatile model doesn't need a data file
+    assert_eq!(
+        manifest.validate(&dir),
+        vec!["the data file for model `default.default` is missing".to_owned()]
+    );
+    fs::create_dir_all(dir.join("default")).unwrap();
+    fs::write(dir.join("default").join("default"), b"").unwrap();
+    assert!(manifest.validate(&dir).is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
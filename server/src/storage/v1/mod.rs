@@ -62,14 +62,20 @@ use {
 mod macros;
 // endof do not mess
 pub mod bytemarks;
+pub mod diff;
 pub mod error;
 pub mod flush;
 pub mod interface;
 pub mod iter;
+pub mod manifest;
 pub mod preload;
+pub mod readahead;
 pub mod sengine;
 pub mod unflush;
 // test
+#[cfg(feature = "nightly")]
+#[cfg(test)]
+mod benches;
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,98 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Read-ahead
+//!
+//! While restoring data, most of the time on slow (or network) volumes is spent waiting for
+//! the table files to be read. The [`Readahead`] reader loads the next few files on a background
+//! thread while the current one is being deserialized
+
+use {
+    crate::IoResult,
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        sync::mpsc::{self, Receiver},
+        thread,
+    },
+};
+
+type Prefetched = (PathBuf, IoResult<Vec<u8>>);
+
+/// A file reader that reads files ahead of their use on a background thread
+///
+/// Files **must be** requested in the same order in which they were provided. If they are not,
+/// then the reader simply stops prefetching and falls back to reading files directly
+pub struct Readahead {
+    rx: Option<Receiver<Prefetched>>,
+}
+
+impl Readahead {
+    /// A reader that doesn't prefetch anything
+    pub const fn disabled() -> Self {
+        Self { rx: None }
+    }
+    /// Start reading the provided `files` in order, keeping at most `depth` files buffered in
+    /// memory. A `depth` of zero disables read-ahead
+    pub fn new(files: Vec<PathBuf>, depth: usize) -> Self {
+        if depth == 0 || files.is_empty() {
+            return Self::disabled();
+        }
+        let (tx, rx) = mpsc::sync_channel(depth);
+        let spawned = thread::Builder::new()
+            .name("readahead".to_owned())
+            .spawn(move || {
+                for file in files {
+                    let data = fs::read(&file);
+                    if tx.send((file, data)).is_err() {
+                        // the reader is gone; no need to read anything else
+                        break;
+                    }
+                }
+            });
+        match spawned {
+            Ok(_) => Self { rx: Some(rx) },
+            Err(e) => {
+                log::warn!("Failed to start read-ahead worker: {e}. Reading files directly");
+                Self::disabled()
+            }
+        }
+    }
+    /// Read the file at the given path, returning prefetched data if available
+    pub fn read(&mut self, path: &Path) -> IoResult<Vec<u8>> {
+        if let Some(rx) = self.rx.as_ref() {
+            match rx.recv() {
+                Ok((prefetched, data)) if prefetched == path => return data,
+                _ => {
+                    // either the files were requested out of order or the worker is gone. dropping
+                    // the receiver will make the worker exit
+                    self.rx = None;
+                }
+            }
+        }
+        fs::read(path)
+    }
+}
@@ -30,11 +30,18 @@ use {
     crate::{
         corestore::{iarray::IArray, lazy::Lazy, lock::QuickLock, memstore::Memstore},
         storage::v1::flush::{LocalSnapshot, RemoteSnapshot},
+        util::os,
     },
     chrono::prelude::Utc,
     core::{fmt, str},
     regex::Regex,
-    std::{collections::HashSet, fs, io::Error as IoError, path::Path, sync::Arc},
+    std::{
+        collections::HashSet,
+        fs,
+        io::{Error as IoError, ErrorKind},
+        path::Path,
+        sync::Arc,
+    },
 };
 
 type QStore = IArray<[String; 64]>;
@@ -90,6 +97,8 @@ pub struct SnapshotEngine {
     local_queue: QuickLock<Queue>,
     /// the remote snapshot lock
     remote_queue: QuickLock<HashSet<Box<[u8]>>>,
+    /// the directory into which older snapshots are moved (they're deleted if this isn't set)
+    archive: Option<Arc<str>>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -103,11 +112,12 @@ pub enum SnapshotActionResult {
 
 impl SnapshotEngine {
     /// Returns a fresh, uninitialized snapshot engine instance
-    pub fn new(maxlen: usize) -> Self {
+    pub fn new(maxlen: usize, archive: Option<String>) -> Self {
         Self {
             local_enabled: true,
             local_queue: QuickLock::new(Queue::new(maxlen, maxlen == 0)),
             remote_queue: QuickLock::new(HashSet::new()),
+            archive: archive.map(Arc::from),
         }
     }
     pub fn new_disabled() -> Self {
@@ -115,6 +125,7 @@ impl SnapshotEngine {
             local_enabled: false,
             local_queue: QuickLock::new(Queue::new(0, true)),
             remote_queue: QuickLock::new(HashSet::new()),
+            archive: None,
         }
     }
     fn _parse_dir(
@@ -173,6 +184,29 @@ impl SnapshotEngine {
         super::flush::flush_full(snapshot, store)?;
         Ok(())
     }
+    /// Move a snapshot into the archive directory. If the archive is on a different filesystem,
+    /// the snapshot is copied and then removed. An archived snapshot with the same name is never
+    /// overwritten
+    fn archive_snapshot(snap: &str, archive: &str) -> Result<(), IoError> {
+        let src = concat_path!(DIR_SNAPROOT, snap);
+        let dst = Path::new(archive).join(snap);
+        fs::create_dir_all(archive)?;
+        if dst.exists() {
+            return Err(IoError::new(
+                ErrorKind::AlreadyExists,
+                format!("`{snap}` is already in the archive"),
+            ));
+        }
+        match fs::rename(&src, &dst) {
+            Ok(()) => {}
+            Err(e) if os::is_cross_device(&e) => {
+                os::recursive_copy(&src, &dst)?;
+                fs::remove_dir_all(&src)?;
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
     /// Spawns a blocking task on a threadpool for blocking tasks. Returns either of:
     /// - `0` => Okay (returned **even if old snap deletion failed**)
     /// - `1` => Error
@@ -207,13 +241,20 @@ impl SnapshotEngine {
                 }
             }
 
-            // Now delete the older snap (if any)
+            // Now delete (or archive) the older snap (if any)
             if let Some(snap) = todel {
-                tokio::task::spawn_blocking(move || {
-                    if let Err(e) = fs::remove_dir_all(concat_path!(DIR_SNAPROOT, snap)) {
-                        log::warn!("Failed to remove older snapshot (ignored): {}", e);
-                    } else {
-                        log::info!("Successfully removed older snapshot");
+                let archive = self.archive.clone();
+                tokio::task::spawn_blocking(move || match archive {
+                    Some(archive) => match Self::archive_snapshot(&snap, &archive) {
+                        Ok(()) => log::info!("Successfully archived older snapshot `{snap}`"),
+                        Err(e) => log::warn!("Failed to archive older snapshot (ignored): {e}"),
+                    },
+                    None => {
+                        if let Err(e) = fs::remove_dir_all(concat_path!(DIR_SNAPROOT, snap)) {
+                            log::warn!("Failed to remove older snapshot (ignored): {}", e);
+                        } else {
+                            log::info!("Successfully removed older snapshot");
+                        }
                     }
                 })
                 .await
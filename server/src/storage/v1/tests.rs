@@ -213,6 +213,44 @@ mod preload_tests {
     }
 }
 
+mod readahead_tests {
+    use super::readahead::Readahead;
+    use std::{fs, path::PathBuf};
+    fn create_files(dir: &str) -> Vec<PathBuf> {
+        fs::create_dir_all(dir).unwrap();
+        (0..4)
+            .map(|i| {
+                let path = PathBuf::from(format!("{dir}/file{i}"));
+                fs::write(&path, format!("contents of file {i}")).unwrap();
+                path
+            })
+            .collect()
+    }
+    #[test]
+    fn test_readahead_in_order() {
+        let files = create_files("data/readahead_in_order");
+        let mut reader = Readahead::new(files.clone(), 2);
+        for (i, file) in files.iter().enumerate() {
+            assert_eq!(
+                reader.read(file).unwrap(),
+                format!("contents of file {i}").into_bytes()
+            );
+        }
+    }
+    #[test]
+    fn test_readahead_out_of_order() {
+        let files = create_files("data/readahead_out_of_order");
+        let mut reader = Readahead::new(files.clone(), 1);
+        // we should fall back to reading the files directly
+        for (i, file) in files.iter().enumerate().rev() {
+            assert_eq!(
+                reader.read(file).unwrap(),
+                format!("contents of file {i}").into_bytes()
+            );
+        }
+    }
+}
+
 mod bytemark_set_tests {
     use super::*;
     use crate::corestore::memstore::{Keyspace, ObjectID};
@@ -429,10 +467,42 @@ mod flush_routines {
             SharedSlice,
         },
         kvengine::LockedVec,
-        storage::v1::{bytemarks, flush::Autoflush, Coremap},
+        storage::v1::{
+            bytemarks,
+            flush::{Autoflush, RemoteSnapshot},
+            Coremap,
+        },
     };
     use std::fs;
     #[test]
+    fn test_flush_unavailable_table() {
+        let tbl = Table::new_default_kve();
+        tbl.get_kvstore()
+            .unwrap()
+            .set("hello".into(), "world".into())
+            .unwrap();
+        let tblid = unsafe { ObjectID::from_slice("myskippedtbl") };
+        let ksid = unsafe { ObjectID::from_slice("myskippedks") };
+        fs::create_dir_all("data/ks/myskippedks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let on_disk = fs::read("data/ks/myskippedks/myskippedtbl").unwrap();
+        // the data for this table was never loaded, so the file must be left untouched
+        let unavailable = Table::new_unavailable(bytemarks::BYTEMARK_MODEL_KV_BIN_BIN, false);
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &unavailable).unwrap();
+        assert_eq!(
+            fs::read("data/ks/myskippedks/myskippedtbl").unwrap(),
+            on_disk
+        );
+        // and snapshots should carry the file over as-is
+        fs::create_dir_all("data/rsnap/myskippedsnap/myskippedks").unwrap();
+        let target = RemoteSnapshot::new("myskippedsnap");
+        super::flush::oneshot::flush_table(&target, &tblid, &ksid, &unavailable).unwrap();
+        assert_eq!(
+            fs::read("data/rsnap/myskippedsnap/myskippedks/myskippedtbl").unwrap(),
+            on_disk
+        );
+    }
+    #[test]
     fn test_flush_unflush_table_pure_kve() {
         let tbl = Table::new_default_kve();
         tbl.get_kvstore()
@@ -526,7 +596,7 @@ mod flush_routines {
 
         // now flush it
         super::flush::flush_keyspace_full(&Autoflush, &ksid, &ks).unwrap();
-        let ret = super::unflush::read_keyspace::<Keyspace>(&ksid).unwrap();
+        let ret = super::unflush::read_keyspace::<Keyspace>(&ksid, 2).unwrap();
         let tbl1_ret = ret.tables.get(&tbl1).unwrap();
         let tbl2_ret = ret.tables.get(&tbl2).unwrap();
         let tbl3_ret_list = ret.tables.get(&list_tbl).unwrap();
@@ -766,6 +836,8 @@ mod storage_target_directory_structure {
             FileKind::File("system/superauthy"),
             // the preload file
             FileKind::File("PRELOAD"),
+            // the backup manifest
+            FileKind::File("MANIFEST"),
         ];
         (get_memstore(), paths)
     }
@@ -826,3 +898,84 @@ mod storage_target_directory_structure {
         fs::remove_dir_all("data/rsnap/wisnap").unwrap();
     }
 }
+
+mod diff_tests {
+    use crate::{
+        corestore::{
+            memstore::{Memstore, ObjectID},
+            table::Table,
+        },
+        storage::v1::{
+            diff,
+            flush::{self, RemoteSnapshot},
+        },
+    };
+    use std::{fs, path::Path};
+    #[test]
+    fn test_diff_backup() {
+        let store = Memstore::new_default();
+        let default = store.get_keyspace_atomic_ref("default".as_bytes()).unwrap();
+        let tbl = default.get_table_atomic_ref("default".as_bytes()).unwrap();
+        tbl.get_kvstore()
+            .unwrap()
+            .set("hello".into(), "world".into())
+            .unwrap();
+        flush::flush_full(RemoteSnapshot::new("diffsnap"), &store).unwrap();
+        let backup = Path::new("data/rsnap/diffsnap");
+        assert!(diff::diff_backup(backup, &store).unwrap().is_empty());
+        // now let the live data drift
+        tbl.get_kvstore()
+            .unwrap()
+            .update("hello".into(), "universe".into())
+            .unwrap();
+        assert!(default.create_table(
+            ObjectID::try_from_slice("drifted").unwrap(),
+            Table::new_default_kve()
+        ));
+        assert_eq!(
+            diff::diff_backup(backup, &store).unwrap(),
+            vec![
+                "model `default.drifted` is missing in the backup".to_owned(),
+                "model `default.default` has 1 of 1 sampled rows that differ".to_owned(),
+            ]
+        );
+        fs::remove_dir_all("data/rsnap/diffsnap").unwrap();
+    }
+}
+
+mod memory_estimate {
+    use super::{error::StorageEngineError, unflush::check_memory_estimate};
+    use crate::config::SkipLoadSet;
+    use std::fs;
+    const ROOT: &str = "data/memestimate";
+    fn skip(entries: &str) -> SkipLoadSet {
+        entries.parse().unwrap()
+    }
+    #[test]
+    fn test_memory_limit() {
+        let _ = fs::remove_dir_all(ROOT);
+        fs::create_dir_all(format!("{ROOT}/bigks")).unwrap();
+        fs::create_dir_all(format!("{ROOT}/smallks")).unwrap();
+        // 3 MiB on disk, so about 6 MiB in memory
+        fs::write(format!("{ROOT}/bigks/bigtbl"), vec![0u8; 3 * 1024 * 1024]).unwrap();
+        fs::write(format!("{ROOT}/smallks/smalltbl"), b"hello").unwrap();
+        let none = SkipLoadSet::new_empty();
+        assert!(matches!(
+            check_memory_estimate(ROOT, 1, false, &none),
+            Err(StorageEngineError::OverMemoryLimit {
+                estimate: 6,
+                memlimit: 1
+            })
+        ));
+        // no limit, or a forced load
+        check_memory_estimate(ROOT, 0, false, &none).unwrap();
+        check_memory_estimate(ROOT, 1, true, &none).unwrap();
+        check_memory_estimate(ROOT, 6, false, &none).unwrap();
+        // the skipped data isn't loaded, so it doesn't count
+        check_memory_estimate(ROOT, 1, false, &skip("bigks")).unwrap();
+        check_memory_estimate(ROOT, 1, false, &skip("bigks.bigtbl")).unwrap();
+        check_memory_estimate(ROOT, 1, false, &skip("bigks,bigks.bigtbl")).unwrap();
+        assert!(check_memory_estimate(ROOT, 1, false, &skip("smallks")).is_err());
+        fs::remove_dir_all(ROOT).unwrap();
+    }
+}
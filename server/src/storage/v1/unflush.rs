@@ -31,6 +31,7 @@
 use {
     super::bytemarks,
     crate::{
+        config::{SkipLoadSet, StorageSettings},
         corestore::{
             memstore::{Keyspace, Memstore, ObjectID, SystemKeyspace, SYSTEM},
             table::{SystemTable, Table},
@@ -41,32 +42,49 @@ use {
             flush::Autoflush,
             interface::DIR_KSROOT,
             preload::LoadedPartfile,
+            readahead::Readahead,
             Coremap,
         },
-        util::Wrapper,
+        util::{os, Wrapper},
     },
     core::mem::transmute,
-    std::{fs, io::ErrorKind, path::Path, sync::Arc},
+    std::{
+        collections::HashSet,
+        fs,
+        io::ErrorKind,
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
 };
 
-type PreloadSet = std::collections::HashSet<ObjectID>;
+type PreloadSet = HashSet<ObjectID>;
 const PRELOAD_PATH: &str = "data/ks/PRELOAD";
+/// A (rather generous) estimate of how much larger data is in memory as compared to its size on disk
+const MEMORY_ESTIMATE_FACTOR: u64 = 2;
+const MIB: u64 = 1024 * 1024;
 
 /// A keyspace that can be restored from disk storage
 pub trait UnflushableKeyspace: Sized {
-    /// Unflush routine for a keyspace
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self>;
+    /// Unflush routine for a keyspace. Up to `readahead` table files are read in the background
+    /// while the tables are being restored
+    fn unflush_keyspace(
+        partmap: LoadedPartfile,
+        ksid: &ObjectID,
+        readahead: usize,
+    ) -> StorageEngineResult<Self>;
 }
 
 impl UnflushableKeyspace for Keyspace {
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self> {
+    fn unflush_keyspace(
+        partmap: LoadedPartfile,
+        ksid: &ObjectID,
+        readahead: usize,
+    ) -> StorageEngineResult<Self> {
         let ks: Coremap<ObjectID, Arc<Table>> = Coremap::with_capacity(partmap.len());
-        for (tableid, (table_storage_type, model_code)) in partmap.into_iter() {
-            if table_storage_type > 1 {
-                return Err(StorageEngineError::bad_metadata_in_table(ksid, &tableid));
-            }
-            let is_volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
-            let tbl = self::read_table::<Table>(ksid, &tableid, is_volatile, model_code)?;
+        let (tables, mut reader) = self::prepare_partmap(partmap, ksid, readahead)?;
+        for (tableid, is_volatile, model_code) in tables {
+            let filepath = self::table_path(ksid, &tableid);
+            let tbl = Table::unflush_table(filepath, model_code, is_volatile, &mut reader)?;
             ks.true_if_insert(tableid, Arc::new(tbl));
         }
         Ok(Keyspace::init_with_all_def_strategy(ks))
@@ -74,20 +92,46 @@ impl UnflushableKeyspace for Keyspace {
 }
 
 impl UnflushableKeyspace for SystemKeyspace {
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self> {
+    fn unflush_keyspace(
+        partmap: LoadedPartfile,
+        ksid: &ObjectID,
+        readahead: usize,
+    ) -> StorageEngineResult<Self> {
         let ks: Coremap<ObjectID, Wrapper<SystemTable>> = Coremap::with_capacity(partmap.len());
-        for (tableid, (table_storage_type, model_code)) in partmap.into_iter() {
-            if table_storage_type > 1 {
-                return Err(StorageEngineError::bad_metadata_in_table(ksid, &tableid));
-            }
-            let is_volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
-            let tbl = self::read_table::<SystemTable>(ksid, &tableid, is_volatile, model_code)?;
+        let (tables, mut reader) = self::prepare_partmap(partmap, ksid, readahead)?;
+        for (tableid, is_volatile, model_code) in tables {
+            let filepath = self::table_path(ksid, &tableid);
+            let tbl = SystemTable::unflush_table(filepath, model_code, is_volatile, &mut reader)?;
             ks.true_if_insert(tableid, Wrapper::new(tbl));
         }
         Ok(SystemKeyspace::new(ks))
     }
 }
 
+/// Validate the partmap, returning the tables (in the order in which they should be restored)
+/// along with a reader that has already started reading the persistent tables
+fn prepare_partmap(
+    partmap: LoadedPartfile,
+    ksid: &ObjectID,
+    readahead: usize,
+) -> StorageEngineResult<(Vec<(ObjectID, bool, u8)>, Readahead)> {
+    let mut tables = Vec::with_capacity(partmap.len());
+    for (tableid, (table_storage_type, model_code)) in partmap.into_iter() {
+        if table_storage_type > 1 {
+            return Err(StorageEngineError::bad_metadata_in_table(ksid, &tableid));
+        }
+        let is_volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
+        tables.push((tableid, is_volatile, model_code));
+    }
+    // volatile tables are never read from disk, so don't prefetch them
+    let files = tables
+        .iter()
+        .filter(|(_, is_volatile, _)| !is_volatile)
+        .map(|(tableid, _, _)| self::table_path(ksid, tableid))
+        .collect();
+    Ok((tables, Readahead::new(files, readahead)))
+}
+
 /// Tables that can be restored from disk storage
 pub trait UnflushableTable: Sized {
     /// Procedure to restore (deserialize) table from disk storage
@@ -95,6 +139,7 @@ pub trait UnflushableTable: Sized {
         filepath: impl AsRef<Path>,
         model_code: u8,
         volatile: bool,
+        reader: &mut Readahead,
     ) -> StorageEngineResult<Self>;
 }
 
@@ -104,11 +149,12 @@ impl UnflushableTable for Table {
         filepath: impl AsRef<Path>,
         model_code: u8,
         volatile: bool,
+        reader: &mut Readahead,
     ) -> StorageEngineResult<Self> {
         let ret = match model_code {
             // pure KVEBlob: [0, 3]
             x if x < 4 => {
-                let data = decode(filepath, volatile)?;
+                let data = decode(filepath, volatile, reader)?;
                 let (k_enc, v_enc) = unsafe {
                     // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
                     let key: bool = transmute(model_code >> 1);
@@ -119,7 +165,7 @@ impl UnflushableTable for Table {
             }
             // KVExtlistmap: [4, 7]
             x if x < 8 => {
-                let data = decode(filepath, volatile)?;
+                let data = decode(filepath, volatile, reader)?;
                 let (k_enc, v_enc) = unsafe {
                     // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
                     let code = model_code - 4;
@@ -144,11 +190,12 @@ impl UnflushableTable for SystemTable {
         filepath: impl AsRef<Path>,
         model_code: u8,
         volatile: bool,
+        reader: &mut Readahead,
     ) -> StorageEngineResult<Self> {
         match model_code {
             0 => {
                 // this is the authmap
-                let authmap = decode(filepath, volatile)?;
+                let authmap = decode(filepath, volatile, reader)?;
                 Ok(SystemTable::new_auth(Arc::new(authmap)))
             }
             _ => Err(StorageEngineError::BadMetadata(
@@ -162,11 +209,12 @@ impl UnflushableTable for SystemTable {
 fn decode<T: DeserializeInto>(
     filepath: impl AsRef<Path>,
     volatile: bool,
+    reader: &mut Readahead,
 ) -> StorageEngineResult<T> {
     if volatile {
         Ok(T::new_empty())
     } else {
-        let data = fs::read(filepath.as_ref()).map_err_context(format!(
+        let data = reader.read(filepath.as_ref()).map_err_context(format!(
             "reading file {}",
             filepath.as_ref().to_string_lossy()
         ))?;
@@ -186,21 +234,76 @@ pub fn read_table<T: UnflushableTable>(
     volatile: bool,
     model_code: u8,
 ) -> StorageEngineResult<T> {
-    let filepath = unsafe { concat_path!(DIR_KSROOT, ksid.as_str(), tblid.as_str()) };
-    let tbl = T::unflush_table(filepath, model_code, volatile)?;
+    let filepath = self::table_path(ksid, tblid);
+    let tbl = T::unflush_table(filepath, model_code, volatile, &mut Readahead::disabled())?;
     Ok(tbl)
 }
 
+/// Returns the path to the data file of the given table
+pub fn table_path(ksid: &ObjectID, tblid: &ObjectID) -> PathBuf {
+    unsafe { concat_path!(DIR_KSROOT, ksid.as_str(), tblid.as_str()) }
+}
+
 /// Read an entire keyspace into a Coremap. You'll need to initialize the rest
-pub fn read_keyspace<K: UnflushableKeyspace>(ksid: &ObjectID) -> StorageEngineResult<K> {
+///
+/// Up to `readahead` table files are read ahead of time (`0` disables this)
+pub fn read_keyspace<K: UnflushableKeyspace>(
+    ksid: &ObjectID,
+    readahead: usize,
+) -> StorageEngineResult<K> {
     let partmap = self::read_partmap(ksid)?;
-    K::unflush_keyspace(partmap, ksid)
+    K::unflush_keyspace(partmap, ksid, readahead)
+}
+
+/// Read an entire keyspace, except for the data of the models that are to be skipped. Skipped
+/// models are added as unavailable tables so that they (and their data on disk) are preserved
+fn read_keyspace_skipping(
+    ksid: &ObjectID,
+    readahead: usize,
+    skip_load: &SkipLoadSet,
+) -> StorageEngineResult<Keyspace> {
+    let mut partmap = self::read_partmap(ksid)?;
+    let ksname = unsafe { ksid.as_str() };
+    let skipped: Vec<ObjectID> = partmap
+        .keys()
+        .filter(|tblid| skip_load.skips_model(ksname, unsafe { tblid.as_str() }))
+        .cloned()
+        .collect();
+    let skipped: Vec<(ObjectID, (u8, u8))> = skipped
+        .into_iter()
+        .filter_map(|tblid| partmap.remove_entry(&tblid))
+        .collect();
+    let ks = Keyspace::unflush_keyspace(partmap, ksid, readahead)?;
+    for (tblid, (table_storage_type, model_code)) in skipped {
+        if table_storage_type > 1 {
+            return Err(StorageEngineError::bad_metadata_in_table(ksid, &tblid));
+        }
+        let is_volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
+        log::warn!(
+            "Skipped loading `{ksname}.{}`. It will be unavailable",
+            unsafe { tblid.as_str() }
+        );
+        ks.tables.true_if_insert(
+            tblid,
+            Arc::new(Table::new_unavailable(model_code, is_volatile)),
+        );
+    }
+    Ok(ks)
 }
 
 /// Read the `PARTMAP` for a given keyspace
 pub fn read_partmap(ksid: &ObjectID) -> StorageEngineResult<LoadedPartfile> {
+    self::read_partmap_in(DIR_KSROOT, ksid)
+}
+
+/// Read the `PARTMAP` for a given keyspace from a directory that is laid out like
+/// [`DIR_KSROOT`] (for example, a snapshot)
+pub fn read_partmap_in(
+    root: impl AsRef<Path>,
+    ksid: &ObjectID,
+) -> StorageEngineResult<LoadedPartfile> {
     let ksid_str = unsafe { ksid.as_str() };
-    let filepath = concat_path!(DIR_KSROOT, ksid_str, "PARTMAP");
+    let filepath = root.as_ref().join(ksid_str).join("PARTMAP");
     let partmap_raw = fs::read(&filepath)
         .map_err_context(format!("while reading {}", filepath.to_string_lossy()))?;
     super::de::deserialize_set_ctype_bytemark(&partmap_raw)
@@ -213,12 +316,28 @@ pub fn read_preload() -> StorageEngineResult<PreloadSet> {
     super::preload::read_preload_raw(read)
 }
 
+/// Read the `PRELOAD` from a directory that is laid out like [`DIR_KSROOT`]
+pub fn read_preload_in(root: impl AsRef<Path>) -> StorageEngineResult<PreloadSet> {
+    let read = fs::read(root.as_ref().join("PRELOAD")).map_err_context("reading PRELOAD")?;
+    super::preload::read_preload_raw(read)
+}
+
 /// Read everything and return a [`Memstore`]
 ///
 /// If this is a new instance an empty store is returned while the directory tree
 /// is also created. If this is an already initialized instance then the store
-/// is read and returned (and any possible errors that are encountered are returned)
-pub fn read_full() -> StorageEngineResult<Memstore> {
+/// is read and returned (and any possible errors that are encountered are returned). Up to
+/// `readahead` table files are prefetched while the keyspaces are being restored
+///
+/// Before anything is read, the memory required by the data is estimated and if it is over the
+/// configured limit, we refuse to load the data (unless we're told to load it anyway)
+pub fn read_full(settings: &StorageSettings) -> StorageEngineResult<Memstore> {
+    let StorageSettings {
+        readahead,
+        memlimit,
+        force_load,
+        ref skip_load,
+    } = *settings;
     if is_new_instance()? {
         log::trace!("Detected new instance. Creating data directory");
         /*
@@ -239,20 +358,92 @@ pub fn read_full() -> StorageEngineResult<Memstore> {
         super::flush::flush_full(target, &store)?;
         return Ok(store);
     }
+    self::check_memory_estimate(DIR_KSROOT, memlimit, force_load, skip_load)?;
     let mut preload = self::read_preload()?;
     // HACK(@ohsayan): Pop off the preload from the serial read_keyspace list. It will fail
     assert!(preload.remove(&SYSTEM));
-    let system_keyspace = self::read_keyspace::<SystemKeyspace>(&SYSTEM)?;
+    let system_keyspace = self::read_keyspace::<SystemKeyspace>(&SYSTEM, readahead)?;
     let ksmap = Coremap::with_capacity(preload.len());
     for ksid in preload {
-        let ks = self::read_keyspace::<Keyspace>(&ksid)?;
+        let ks = if skip_load.is_empty() {
+            self::read_keyspace::<Keyspace>(&ksid, readahead)?
+        } else {
+            self::read_keyspace_skipping(&ksid, readahead, skip_load)?
+        };
         ksmap.upsert(ksid, Arc::new(ks));
     }
+    for (space, model) in skip_load.entries() {
+        let found = ksmap.get(space.as_bytes()).map_or(false, |ks| {
+            model.map_or(true, |model| ks.tables.contains_key(model.as_bytes()))
+        });
+        if !found {
+            log::warn!(
+                "`{}` was asked to be skipped but no such space or model exists",
+                model.map_or_else(|| space.to_owned(), |model| format!("{space}.{model}"))
+            );
+        }
+    }
     // HACK(@ohsayan): Now pop system back in here
     ksmap.upsert(SYSTEM, Arc::new(Keyspace::empty()));
     Ok(Memstore::init_with_all(ksmap, system_keyspace))
 }
 
+/// Estimate the amount of memory (in MiB) that will be needed to hold the data in `root`,
+/// leaving out the spaces and models that won't be loaded
+pub(super) fn estimate_memory_usage(
+    root: &str,
+    skip_load: &SkipLoadSet,
+) -> StorageEngineResult<u64> {
+    let mut disk_size = os::dirsize(root).map_err_context("estimating size of data")?;
+    // a model is already accounted for if its whole space is skipped
+    let skipped: HashSet<String> = skip_load
+        .entries()
+        .filter(|(space, model)| model.is_none() || !skip_load.skips_space(space))
+        .map(|(space, model)| match model {
+            Some(model) => format!("{root}/{space}/{model}"),
+            None => format!("{root}/{space}"),
+        })
+        .collect();
+    for path in skipped {
+        let size = match fs::metadata(&path) {
+            Ok(meta) if meta.is_dir() => os::dirsize(&path),
+            Ok(meta) => Ok(meta.len()),
+            // there's nothing to leave out
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e),
+        }
+        .map_err_context("estimating size of skipped data")?;
+        disk_size = disk_size.saturating_sub(size);
+    }
+    Ok((disk_size * MEMORY_ESTIMATE_FACTOR) / MIB)
+}
+
+/// Make sure that the estimated memory usage of the data in `root` is within `memlimit` MiB
+/// (`0` means that there is no limit). If `force_load` is set, we'll simply warn if we're over
+/// the limit
+pub(super) fn check_memory_estimate(
+    root: &str,
+    memlimit: u64,
+    force_load: bool,
+    skip_load: &SkipLoadSet,
+) -> StorageEngineResult<()> {
+    if memlimit == 0 {
+        return Ok(());
+    }
+    let estimate = self::estimate_memory_usage(root, skip_load)?;
+    log::info!("Estimated memory usage for data: {estimate} MiB (limit: {memlimit} MiB)");
+    if estimate > memlimit {
+        if force_load {
+            log::warn!(
+                "Estimated memory usage of {estimate} MiB exceeds the limit of {memlimit} MiB. Loading anyway because a forced load was requested"
+            );
+        } else {
+            return Err(StorageEngineError::OverMemoryLimit { estimate, memlimit });
+        }
+    }
+    Ok(())
+}
+
 /// Check if the `data` directory is non-empty (if not: we're on a new instance)
 pub fn is_new_instance() -> StorageEngineResult<bool> {
     match fs::read_dir("data") {
@@ -55,6 +55,21 @@ mod __private {
             Element::RespCode(RespCode::Okay)
         );
     }
+    async fn test_check_does_not_execute() {
+        let mut rng = rand::thread_rng();
+        let ksname = utils::rand_alphastring(10, &mut rng);
+        query.push(format!("check create space {ksname}"));
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push(format!("check drop space {ksname}"));
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("container-not-found".to_owned()))
+        );
+    }
     async fn test_create_table() {
         let mut rng = rand::thread_rng();
         let tblname = utils::rand_alphastring(10, &mut rng);
@@ -73,6 +88,24 @@ mod __private {
             Element::RespCode(RespCode::Okay)
         );
     }
+    async fn test_create_temp() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        query.push(format!("create temp model {tblname}(string, string)"));
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("err-already-exists".to_owned()))
+        );
+        let query = Query::from(format!("drop model {tblname}"));
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
     async fn test_create_table_fully_qualified_entity() {
         let mut rng = rand::thread_rng();
         let tblname = utils::rand_alphastring(10, &mut rng);
@@ -131,6 +131,150 @@ mod sys {
             Element::UnsignedInt
         )
     }
+    #[dbtest]
+    async fn sys_report_storage() {
+        runmatch!(con, query!("sys", "report", "storage"), Element::Array)
+    }
+    #[dbtest]
+    async fn sys_report_health() {
+        runmatch!(con, query!("sys", "report", "health"), Element::Array)
+    }
+    #[dbtest]
+    async fn sys_report_unknown() {
+        runeq!(
+            con,
+            query!("sys", "report", "journal"),
+            Element::RespCode(RespCode::ErrorString("unknown-report".into()))
+        )
+    }
+    #[dbtest]
+    async fn sys_fence_stale_token() {
+        let token = match con.run_query_raw(&query!("sys", "fence", "acquire")).await {
+            Ok(Element::UnsignedInt(token)) => token,
+            x => panic!("expected a fence token, got {:?}", x),
+        };
+        runeq!(
+            con,
+            query!("fence", token.to_string()),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(
+            con,
+            query!("set", "fenced", "value"),
+            Element::RespCode(RespCode::Okay)
+        );
+        // a newer fence makes our token stale
+        runmatch!(con, query!("sys", "fence", "acquire"), Element::UnsignedInt);
+        runeq!(
+            con,
+            query!("set", "fenced", "value"),
+            Element::RespCode(RespCode::ErrorString("err-fenced".into()))
+        );
+        runeq!(
+            con,
+            query!("fence", token.to_string()),
+            Element::RespCode(RespCode::ErrorString("err-fenced".into()))
+        );
+        runeq!(
+            con,
+            query!("sys", "fence", "release", token.to_string()),
+            Element::RespCode(RespCode::ErrorString("err-fenced".into()))
+        );
+        // reads are never fenced
+        runeq!(
+            con,
+            query!("get", "fenced"),
+            Element::String("value".to_owned())
+        );
+    }
+    #[dbtest]
+    async fn sys_flush_all() {
+        runeq!(
+            con,
+            query!("sys", "flush"),
+            Element::RespCode(RespCode::Okay)
+        )
+    }
+    #[dbtest]
+    async fn sys_flush_model() {
+        runeq!(
+            con,
+            query!("sys", "flush", __MYENTITY__),
+            Element::RespCode(RespCode::Okay)
+        )
+    }
+    #[dbtest]
+    async fn sys_flush_model_nonexistent() {
+        runeq!(
+            con,
+            query!("sys", "flush", "this_model_does_not_exist"),
+            Element::RespCode(RespCode::ErrorString("container-not-found".into()))
+        )
+    }
+    #[dbtest]
+    async fn sys_ddl_model() {
+        let ddl = format!("create model {__MYENTITY__}(string, string) volatile");
+        runeq!(con, query!("sys", "ddl", __MYENTITY__), Element::String(ddl))
+    }
+    #[dbtest]
+    async fn sys_ddl_model_nonexistent() {
+        runeq!(
+            con,
+            query!("sys", "ddl", "this_model_does_not_exist"),
+            Element::RespCode(RespCode::ErrorString("container-not-found".into()))
+        )
+    }
+    // NOTE: we don't test a successful quiesce here since it would pause writes for every other
+    // test running against the same server
+    #[dbtest]
+    async fn sys_quiesce_end_not_quiesced() {
+        runeq!(
+            con,
+            query!("sys", "quiesce", "end"),
+            Element::RespCode(RespCode::ErrorString("not-quiesced".into()))
+        )
+    }
+    #[dbtest]
+    async fn sys_quiesce_begin_bad_timeout() {
+        runeq!(
+            con,
+            query!("sys", "quiesce", "begin", "soon"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+        runeq!(
+            con,
+            query!("sys", "quiesce", "begin", "0"),
+            Element::RespCode(RespCode::Wrongtype)
+        )
+    }
+    // NOTE: we don't test a successful capture here since only one capture can be active at a
+    // time (and it would capture queries from every other test)
+    #[dbtest]
+    async fn sys_capture_stop_not_capturing() {
+        runeq!(
+            con,
+            query!("sys", "capture", "stop"),
+            Element::RespCode(RespCode::ErrorString("not-capturing".into()))
+        )
+    }
+    #[dbtest]
+    async fn sys_capture_start_bad_args() {
+        runeq!(
+            con,
+            query!("sys", "capture", "start", "../../etc/passwd"),
+            Element::RespCode(RespCode::ActionError)
+        );
+        runeq!(
+            con,
+            query!("sys", "capture", "start", "mycapture", "0"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+        runeq!(
+            con,
+            query!("sys", "capture", "start", "mycapture", "1", "unhashed"),
+            Element::RespCode(RespCode::ActionError)
+        )
+    }
 }
 
 use skytable::{query, Element, RespCode};
@@ -39,8 +39,12 @@ mod unix {
     use {
         libc::{rlimit, RLIMIT_NOFILE},
         std::{
+            ffi::CString,
             future::Future,
-            io::Error as IoError,
+            io::{Error as IoError, ErrorKind},
+            mem::MaybeUninit,
+            os::unix::ffi::OsStrExt,
+            path::Path,
             pin::Pin,
             task::{Context, Poll},
         },
@@ -95,6 +99,33 @@ mod unix {
         let _ = ResourceLimit::get().unwrap();
     }
 
+    /// Returns the number of bytes available (to unprivileged users) on the filesystem that
+    /// holds `path`
+    pub fn free_disk_space(path: impl AsRef<Path>) -> Result<u64, IoError> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|e| IoError::new(ErrorKind::InvalidInput, e))?;
+        unsafe {
+            let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+            if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+                return Err(IoError::last_os_error());
+            }
+            let stat = stat.assume_init();
+            // clippy doesn't realize that these have a different size on some platforms
+            #[allow(clippy::useless_conversion)]
+            Ok(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+        }
+    }
+
+    #[test]
+    fn test_free_disk_space() {
+        let _ = free_disk_space(".").unwrap();
+    }
+
+    /// Returns true if the error is because a rename crossed filesystems
+    pub fn is_cross_device(e: &IoError) -> bool {
+        e.raw_os_error() == Some(libc::EXDEV)
+    }
+
     pub struct TerminationSignal {
         sigint: Signal,
         sigterm: Signal,
@@ -127,12 +158,51 @@ mod windows {
     use {
         std::{
             future::Future,
+            io::Error as IoError,
+            mem,
+            os::windows::ffi::OsStrExt,
+            path::Path,
             pin::Pin,
+            ptr,
             task::{Context, Poll},
         },
         tokio::signal::windows::{ctrl_break, ctrl_c, CtrlBreak, CtrlC},
+        winapi::um::{fileapi::GetDiskFreeSpaceExW, winnt::ULARGE_INTEGER},
     };
 
+    /// Returns the number of bytes available (to the current user) on the volume that holds
+    /// `path`
+    pub fn free_disk_space(path: impl AsRef<Path>) -> Result<u64, IoError> {
+        let path: Vec<u16> = path
+            .as_ref()
+            .as_os_str()
+            .encode_wide()
+            .chain(Some(0))
+            .collect();
+        unsafe {
+            // UNSAFE(@ohsayan): Interfacing with low-level winapi stuff, and we know what's happening here :D
+            let mut available: ULARGE_INTEGER = mem::zeroed();
+            let ret = GetDiskFreeSpaceExW(
+                path.as_ptr(),
+                &mut available,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if ret == 0 {
+                Err(IoError::last_os_error())
+            } else {
+                Ok(*available.QuadPart())
+            }
+        }
+    }
+
+    /// Returns true if the error is because a rename crossed volumes
+    pub fn is_cross_device(e: &IoError) -> bool {
+        // this is `ERROR_NOT_SAME_DEVICE`
+        const ERROR_NOT_SAME_DEVICE: i32 = 17;
+        e.raw_os_error() == Some(ERROR_NOT_SAME_DEVICE)
+    }
+
     pub struct TerminationSignal {
         ctrl_c: CtrlC,
         ctrl_break: CtrlBreak,
@@ -158,6 +228,39 @@ mod windows {
     }
 }
 
+/// Copy a file, cloning it (so that both files share the same blocks on disk until one of them
+/// is modified) if the filesystem supports it and falling back to a regular copy otherwise.
+/// On Linux we ask for a clone with `FICLONE` (btrfs, XFS and friends). Elsewhere, `fs::copy`
+/// already does the best that it can (on macOS, it clones files on APFS)
+pub fn copy_file(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> IoResult<u64> {
+    #[cfg(target_os = "linux")]
+    if let Some(len) = self::clone_file(src.as_ref(), dst.as_ref())? {
+        return Ok(len);
+    }
+    fs::copy(src, dst)
+}
+
+#[cfg(target_os = "linux")]
+/// Attempt to clone `src` into `dst`, returning `None` if the filesystem can't do it
+fn clone_file(src: &Path, dst: &Path) -> IoResult<Option<u64>> {
+    use std::os::unix::io::AsRawFd;
+    // this is `_IOW(0x94, 9, int)`. on the few architectures that encode ioctls differently,
+    // the ioctl simply fails and we fall back to a regular copy
+    const FICLONE: u32 = 0x40049409;
+    let src_file = fs::File::open(src)?;
+    let metadata = src_file.metadata()?;
+    let dst_file = fs::File::create(dst)?;
+    let ret = unsafe { libc::ioctl(dst_file.as_raw_fd(), FICLONE as _, src_file.as_raw_fd()) };
+    if ret == 0 {
+        dst_file.set_permissions(metadata.permissions())?;
+        Ok(Some(metadata.len()))
+    } else {
+        // usually because the filesystem doesn't support reflinks or because the files are on
+        // different filesystems
+        Ok(None)
+    }
+}
+
 /// Recursively copy files from the given `src` to the provided `dest`
 pub fn recursive_copy(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> IoResult<()> {
     fs::create_dir_all(&dst)?;
@@ -170,7 +273,7 @@ pub fn recursive_copy(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> IoResult<
             }
             _ => {
                 // this directory has files (or symlinks?)
-                fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
+                copy_file(entry.path(), dst.as_ref().join(entry.file_name()))?;
             }
         }
     }
@@ -218,6 +321,22 @@ fn rcopy_okay() {
     fs::remove_dir_all("my-backups").unwrap();
 }
 
+#[test]
+fn copy_file_okay() {
+    let data = b"skytable".repeat(1000);
+    fs::write("copy-file-src", &data).unwrap();
+    assert_eq!(
+        copy_file("copy-file-src", "copy-file-dst").unwrap(),
+        data.len() as u64
+    );
+    assert_eq!(fs::read("copy-file-dst").unwrap(), data);
+    // the copy must be independent of the source
+    fs::write("copy-file-src", b"changed").unwrap();
+    assert_eq!(fs::read("copy-file-dst").unwrap(), data);
+    fs::remove_file("copy-file-src").unwrap();
+    fs::remove_file("copy-file-dst").unwrap();
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum EntryKind {
     Directory(String),